@@ -0,0 +1,54 @@
+//! Generates a large collection of DR logs for testing drlogseeker with many rows.
+//!
+//! Usage: `cargo run --release --example generate_logs -- <folder> [count]`
+//!
+//! The logs are spread over per-user download folders like Soulseek creates them, e.g.
+//! `<folder>/user17/Artist 42 - Album 1234/dr.txt`, with DR values between DR4 and DR16.
+//! Every 50th log reports `DRERR`, and every 200th log has no DR line at all.
+
+use std::env::args;
+use std::fs::{create_dir_all, write};
+use std::path::PathBuf;
+use std::process::exit;
+
+/// The number of logs generated if no count is given.
+const DEFAULT_COUNT: usize = 100_000;
+
+fn main() {
+    let mut args = args().skip(1);
+    let Some(folder) = args.next().map(PathBuf::from) else {
+        eprintln!("Usage: generate_logs <folder> [count]");
+        exit(1);
+    };
+    let count = args.next().and_then(|count| count.parse().ok()).unwrap_or(DEFAULT_COUNT);
+
+    // A simple linear congruential generator keeps the collection the same on every run.
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move |bound: u64| {
+        seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % bound
+    };
+    for index in 0..count {
+        let album = folder
+            .join(format!("user{}", next(100)))
+            .join(format!("Artist {} - Album {}", next(2000), index));
+        let dr_line = match index {
+            _ if index % 200 == 0 => String::new(),
+            _ if index % 50 == 0 => "Official DR value: DRERR\n".to_string(),
+            _ => format!("Official DR value: DR{}\n", 4 + next(13)),
+        };
+        let log = format!(
+            "foobar2000 1.6.16 / Dynamic Range Meter 1.1.1\nlog date: 2024-01-01 12:00:00\n\n\
+             --------------------------------------------------------------------------------\n\
+             Analyzed: Artist / Album {}\n\
+             --------------------------------------------------------------------------------\n\n\
+             {}Samplerate:        44100 Hz\nChannels:          2\n",
+            index, dr_line
+        );
+        if let Err(err) = create_dir_all(&album).and_then(|_| write(album.join("dr.txt"), log)) {
+            eprintln!("Failed to write {}: {}", album.display(), err);
+            exit(1);
+        }
+    }
+    println!("Wrote {} logs to {}", count, folder.display());
+}
@@ -1,4 +1,13 @@
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::data::retention::RetentionRule;
+use crate::dr_analyzer::analyzer::ScanControl;
+
+/// Reports a non-fatal problem, such as a folder that could not be read.
+///
+/// The handler can be called from any thread, so that the scan threads can report problems.
+pub type ProblemHandler = Arc<dyn Fn(String) + Send + Sync>;
 
 /// Represents the result of a Dynamic Range (DR) scan for a single audio file.
 #[derive(Clone, Debug)]
@@ -13,15 +22,29 @@ pub struct DRResult {
     /// A flag to indicate whether a scan has been attempted on this file.
     /// This helps distinguish between a pending file (`scanned: false`) and a file that was scanned but resulted in an error (`scanned: true`, `dr_value: None`).
     pub scanned: bool,
+    /// A flag for logs that were picked up by watch mode since the last scan.
+    /// These are shown with a "new" badge in the results list.
+    pub is_new: bool,
+    /// A flag for logs marked by a retention policy.
+    /// These are shown with a "marked" badge in the results list.
+    pub marked: bool,
+    /// A flag for logs that could not be read or contain no DR value line at all.
+    /// Logs reporting `DRERR` are not invalid; both are shown without a DR value.
+    pub invalid: bool,
 }
 
-/// Holds the application's overall state, including settings and scan results.
+/// Holds the state of a workspace, including its scan roots and scan results.
+///
+/// The settings that apply to every workspace are kept separately (see `current_settings`).
 pub struct AppState {
-    /// If `true`, files removed from the list will also be deleted from the filesystem.
-    pub delete_files: bool,
-    /// If `true`, parent folders will be deleted if they become empty after a file is deleted.
-    /// This is typically used in conjunction with `delete_files`.
-    pub delete_folders: bool,
     /// A list of `DRResult` structs, representing all the files loaded into the application and their scan states.
     pub results: Vec<DRResult>,
+    /// The folders, archives and individual log files the user added for scanning.
+    pub roots: Vec<PathBuf>,
+    /// The control of the scan that is currently running, or `None` if no scan is running.
+    pub scan_control: Option<Arc<ScanControl>>,
+    /// The rules of the retention policy, kept while the application is running.
+    pub retention_rules: Vec<RetentionRule>,
+    /// Reports the non-fatal problems of this workspace, such as folders that could not be read.
+    pub report_problem: ProblemHandler,
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::data::app_state::DRResult;
+
+/// The logs beneath a folder and the range of their DR values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FolderStats {
+    /// The number of logs, including those without a DR value.
+    pub logs: usize,
+    pub min: Option<u8>,
+    pub mean: Option<f64>,
+    pub max: Option<u8>,
+}
+
+/// A folder of the scanned tree, or a log in it.
+#[derive(Clone, Debug, Default)]
+pub struct FolderNode {
+    pub path: PathBuf,
+    pub name: String,
+    /// Whether this node is a log rather than a folder.
+    pub is_log: bool,
+    /// The subfolders and logs, sorted by name.
+    pub children: Vec<FolderNode>,
+    /// The statistics of everything beneath the folder, or of the log itself.
+    pub stats: FolderStats,
+}
+
+impl FolderNode {
+
+    /// Creates an empty folder, or a log without statistics.
+    fn new(path: PathBuf, is_log: bool) -> Self {
+        let name = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+        FolderNode { path, name, is_log, children: Vec::new(), stats: FolderStats::default() }
+    }
+
+    /// Returns the child with the given path, adding it if the last child has another path.
+    ///
+    /// The results are inserted in path order, so an existing child is always the last one.
+    fn child(&mut self, path: PathBuf, is_log: bool) -> &mut FolderNode {
+        if self.children.last().is_none_or(|child| child.path != path) {
+            self.children.push(FolderNode::new(path, is_log));
+        }
+        self.children.last_mut().unwrap()
+    }
+
+    /// Computes the statistics of the folder from the DR values of its logs.
+    ///
+    /// Returns the sum and number of the DR values beneath, for the mean of the parent folder.
+    fn aggregate(&mut self, dr_values: &dyn Fn(&Path) -> Option<u8>) -> (u64, usize) {
+        if self.is_log {
+            let dr = dr_values(&self.path);
+            self.stats = FolderStats { logs: 1, min: dr, mean: dr.map(f64::from), max: dr };
+            return dr.map_or((0, 0), |dr| (dr as u64, 1));
+        }
+        let (mut sum, mut count) = (0, 0);
+        let mut stats = FolderStats::default();
+        for child in &mut self.children {
+            let (child_sum, child_count) = child.aggregate(dr_values);
+            sum += child_sum;
+            count += child_count;
+            stats.logs += child.stats.logs;
+            stats.min = stats.min.into_iter().chain(child.stats.min).min();
+            stats.max = stats.max.into_iter().chain(child.stats.max).max();
+        }
+        stats.mean = (count > 0).then(|| sum as f64 / count as f64);
+        self.stats = stats;
+        (sum, count)
+    }
+}
+
+/// Arranges the results in the folder tree beneath the scan roots.
+///
+/// Every root becomes a top-level node with the folders and logs beneath it; a root that is
+/// a log itself stays a single log. Results outside all roots are grouped under their parent
+/// folder. Each folder has the number of logs beneath it and the min, mean and max of their
+/// DR values; logs without a DR value are counted, but do not change the DR range.
+pub fn build_folder_tree(roots: &[PathBuf], results: &[DRResult]) -> Vec<FolderNode> {
+    let mut sorted: Vec<&DRResult> = results.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut tree: Vec<FolderNode> = Vec::new();
+    for result in sorted {
+
+        // The innermost root containing the log, or its parent folder if there is none.
+        let root = roots.iter()
+            .filter(|root| result.path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+            .unwrap_or_else(|| result.path.parent().map(Path::to_path_buf).unwrap_or_default());
+        let top = match tree.iter().position(|node| node.path == root) {
+            Some(index) => &mut tree[index],
+            None => {
+                tree.push(FolderNode::new(root.clone(), root == result.path));
+                tree.last_mut().unwrap()
+            }
+        };
+        if top.is_log {
+            continue;
+        }
+
+        // Walk down the folders between the root and the log, adding the missing ones.
+        let mut node = top;
+        let mut path = root;
+        let components: Vec<_> = result.path.strip_prefix(&path).map(|relative| relative.components().collect()).unwrap_or_default();
+        for (index, component) in components.iter().enumerate() {
+            path = path.join(component);
+            node = node.child(path.clone(), index + 1 == components.len());
+        }
+    }
+
+    let dr_values: HashMap<&Path, Option<u8>> = results.iter().map(|result| (result.path.as_path(), result.dr_value)).collect();
+    tree.sort_by(|a, b| a.path.cmp(&b.path));
+    for node in &mut tree {
+        node.aggregate(&|path| dr_values.get(path).copied().flatten());
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, dr_value: Option<u8>) -> DRResult {
+        let path = PathBuf::from(path);
+        DRResult {
+            dr_value,
+            filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path,
+            scanned: true,
+            is_new: false,
+            marked: false,
+            invalid: dr_value.is_none(),
+        }
+    }
+
+    fn names(nodes: &[FolderNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn folders_aggregate_the_logs_beneath_them() {
+        let roots = [PathBuf::from("/music")];
+        let results = [
+            result("/music/B/CD1/dr.txt", Some(14)),
+            result("/music/A/dr.txt", Some(10)),
+            result("/music/A/broken.log", None),
+            result("/elsewhere/dr.txt", Some(5)),
+        ];
+        let tree = build_folder_tree(&roots, &results);
+
+        assert_eq!(tree.iter().map(|node| node.path.as_path()).collect::<Vec<_>>(), [Path::new("/elsewhere"), Path::new("/music")]);
+        let music = &tree[1];
+        assert_eq!(names(&music.children), ["A", "B"]);
+        assert_eq!(music.stats, FolderStats { logs: 3, min: Some(10), mean: Some(12.0), max: Some(14) });
+
+        let album = &music.children[0];
+        assert_eq!(names(&album.children), ["broken.log", "dr.txt"]);
+        assert!(album.children.iter().all(|log| log.is_log));
+        assert_eq!(album.stats, FolderStats { logs: 2, min: Some(10), mean: Some(10.0), max: Some(10) });
+        assert_eq!(album.children[0].stats, FolderStats { logs: 1, min: None, mean: None, max: None });
+
+        let disc = &music.children[1].children[0];
+        assert_eq!((disc.name.as_str(), disc.is_log), ("CD1", false));
+        assert_eq!(names(&disc.children), ["dr.txt"]);
+
+        // Results outside every root are grouped under their parent folder.
+        assert_eq!(names(&tree[0].children), ["dr.txt"]);
+        assert_eq!(tree[0].stats.logs, 1);
+    }
+
+    #[test]
+    fn a_log_root_stays_a_single_log() {
+        let roots = [PathBuf::from("/music/dr.txt")];
+        let tree = build_folder_tree(&roots, &[result("/music/dr.txt", Some(9))]);
+        assert_eq!(tree.len(), 1);
+        assert!(tree[0].is_log);
+        assert!(tree[0].children.is_empty());
+        assert_eq!(tree[0].stats, FolderStats { logs: 1, min: Some(9), mean: Some(9.0), max: Some(9) });
+    }
+}
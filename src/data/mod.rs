@@ -1 +1,6 @@
-pub mod app_state;
\ No newline at end of file
+pub mod app_state;
+pub mod folder_tree;
+pub mod retention;
+pub mod settings;
+pub mod statistics;
+pub mod tiers;
\ No newline at end of file
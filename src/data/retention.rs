@@ -0,0 +1,128 @@
+use std::fs::metadata;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::data::app_state::DRResult;
+use crate::file_manager::archive::is_virtual_path;
+use crate::file_manager::templates::{physical_path, user_folder};
+
+/// A condition on the DR value of a log.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DrCondition {
+    #[default]
+    Any,
+    /// The log has a DR value lower than the given one.
+    Below(u8),
+    /// The log has a DR value of at least the given one.
+    AtLeast(u8),
+}
+
+/// A condition on whether a DR value could be read from a log.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Validity {
+    #[default]
+    Any,
+    /// The log has a DR value.
+    Valid,
+    /// The log was scanned, but no DR value could be read from it.
+    Invalid,
+}
+
+/// A condition on where a log is stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LogKind {
+    #[default]
+    Any,
+    /// A plain log file on disk.
+    File,
+    /// A log inside a ZIP or TAR archive.
+    Archived,
+}
+
+/// What happens to the logs matched by a rule.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum RetentionAction {
+    /// Flag the logs in the results list, without touching the files.
+    #[default]
+    Mark,
+    /// Move the logs (or the archives containing them) to the trash.
+    Trash,
+    /// Move the album folders of the logs to the trash, with everything inside them.
+    TrashAlbum,
+    /// Move the logs (or the archives containing them) into the given folder.
+    Move(PathBuf),
+}
+
+/// A single rule of a retention policy: a set of conditions and an action.
+///
+/// A log matches a rule if it fulfils all of its conditions. Conditions left at their
+/// defaults match every log.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionRule {
+    pub dr: DrCondition,
+    pub validity: Validity,
+    /// Only match logs last modified more than this many days ago.
+    pub older_than_days: Option<u64>,
+    /// Only match logs in this user folder (see `user_folder`), compared case-insensitively.
+    pub user: Option<String>,
+    pub kind: LogKind,
+    pub action: RetentionAction,
+}
+
+/// A log matched by a retention policy, shown in the preview before anything is changed.
+#[derive(Clone, Debug)]
+pub struct RetentionMatch {
+    pub path: PathBuf,
+    /// The index of the first rule that matched the log.
+    pub rule: usize,
+    pub action: RetentionAction,
+}
+
+impl RetentionRule {
+
+    /// Checks whether a result fulfils all conditions of this rule.
+    pub fn matches(&self, result: &DRResult, roots: &[PathBuf], now: SystemTime) -> bool {
+        let dr_matches = match self.dr {
+            DrCondition::Any => true,
+            DrCondition::Below(limit) => result.dr_value.is_some_and(|dr| dr < limit),
+            DrCondition::AtLeast(limit) => result.dr_value.is_some_and(|dr| dr >= limit),
+        };
+        let validity_matches = match self.validity {
+            Validity::Any => true,
+            Validity::Valid => result.dr_value.is_some(),
+            Validity::Invalid => result.scanned && result.dr_value.is_none(),
+        };
+        let kind_matches = match self.kind {
+            LogKind::Any => true,
+            LogKind::File => !is_virtual_path(&result.path),
+            LogKind::Archived => is_virtual_path(&result.path),
+        };
+        let user_matches = self.user.as_ref().is_none_or(|user| {
+            user_folder(&result.path, roots).is_some_and(|folder| folder.to_lowercase() == user.to_lowercase())
+        });
+        let age_matches = self.older_than_days.is_none_or(|days| {
+            metadata(physical_path(&result.path))
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > Duration::from_secs(days * 24 * 60 * 60))
+        });
+        dr_matches && validity_matches && kind_matches && user_matches && age_matches
+    }
+}
+
+/// Evaluates a retention policy against the current results.
+///
+/// Each scanned result is checked against the rules in order, and the first matching rule
+/// decides its action. Results that match no rule are kept as they are. Pending results
+/// are never matched.
+pub fn evaluate_policy(rules: &[RetentionRule], results: &[DRResult], roots: &[PathBuf]) -> Vec<RetentionMatch> {
+    let now = SystemTime::now();
+    results.iter()
+        .filter(|result| result.scanned)
+        .filter_map(|result| {
+            let rule = rules.iter().position(|rule| rule.matches(result, roots, now))?;
+            Some(RetentionMatch { path: result.path.clone(), rule, action: rules[rule].action.clone() })
+        })
+        .collect()
+}
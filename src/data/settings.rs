@@ -0,0 +1,38 @@
+use std::sync::{PoisonError, RwLock};
+
+/// The settings of the application, shared by every window and workspace.
+///
+/// They are kept while the application is running. Background threads read them as well,
+/// so they are stored behind a lock rather than in the state of a workspace.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Settings {
+    /// If `true`, files removed from the list will also be deleted from the filesystem.
+    pub delete_files: bool,
+    /// If `true`, deleted files are removed permanently instead of being moved to the trash.
+    /// Only has an effect in conjunction with `delete_files`.
+    pub delete_permanently: bool,
+    /// If `true`, parent folders will be deleted if they become empty after a file is deleted.
+    /// This is typically used in conjunction with `delete_files`.
+    pub delete_folders: bool,
+    /// If `true`, every action that changes files on disk is disabled, for browsing shared
+    /// or network drives safely.
+    pub read_only: bool,
+}
+
+/// The settings currently in use.
+static CURRENT_SETTINGS: RwLock<Settings> = RwLock::new(Settings {
+    delete_files: false,
+    delete_permanently: false,
+    delete_folders: false,
+    read_only: false,
+});
+
+/// Returns the settings currently in use.
+pub fn current_settings() -> Settings {
+    *CURRENT_SETTINGS.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Changes the settings of every window and workspace.
+pub fn update_settings(update: impl FnOnce(&mut Settings)) {
+    update(&mut CURRENT_SETTINGS.write().unwrap_or_else(PoisonError::into_inner));
+}
@@ -0,0 +1,139 @@
+use crate::data::app_state::DRResult;
+use crate::data::tiers::{current_tiers, DrTier};
+
+/// The highest DR value with its own histogram bar; higher values are counted in this bar.
+pub const HISTOGRAM_MAX: u8 = 20;
+
+/// An overview of the DR values in the results.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    /// The number of logs per DR value, from DR0 up to `HISTOGRAM_MAX` and above.
+    pub histogram: Vec<usize>,
+    /// The number of logs per DR tier, from the lowest to the highest tier.
+    pub tiers: Vec<(String, usize)>,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    /// The number of logs reporting `DRERR`.
+    pub errors: usize,
+    /// The number of logs that could not be read or contain no DR value.
+    pub invalid: usize,
+    /// The number of logs that have not been scanned yet.
+    pub pending: usize,
+}
+
+impl Statistics {
+
+    /// Computes the statistics of a list of results.
+    ///
+    /// The median of an even number of values is the mean of the two middle values.
+    pub fn compute(results: &[DRResult]) -> Statistics {
+        Statistics::compute_with_tiers(results, &current_tiers())
+    }
+
+    /// Computes the statistics of a list of results, counting the logs per tier of `tier_list`.
+    fn compute_with_tiers(results: &[DRResult], tier_list: &[DrTier]) -> Statistics {
+        let mut histogram = vec![0; HISTOGRAM_MAX as usize + 1];
+        let mut tiers: Vec<(String, usize)> = tier_list.iter().map(|tier| (tier.name.clone(), 0)).collect();
+        let mut values = Vec::new();
+        let (mut errors, mut invalid, mut pending) = (0, 0, 0);
+        for result in results {
+            match result.dr_value {
+                Some(dr) => {
+                    histogram[dr.min(HISTOGRAM_MAX) as usize] += 1;
+                    if let Some(index) = tier_list.iter().rposition(|tier| tier.min_dr <= dr) {
+                        tiers[index].1 += 1;
+                    }
+                    values.push(dr);
+                }
+                None if !result.scanned => pending += 1,
+                None if result.invalid => invalid += 1,
+                None => errors += 1,
+            }
+        }
+        values.sort_unstable();
+        let mean = (!values.is_empty()).then(|| values.iter().map(|&dr| dr as f64).sum::<f64>() / values.len() as f64);
+        let median = (!values.is_empty()).then(|| {
+            let middle = values.len() / 2;
+            if values.len() % 2 == 0 {
+                (values[middle - 1] as f64 + values[middle] as f64) / 2.0
+            } else {
+                values[middle] as f64
+            }
+        });
+        Statistics { histogram, tiers, mean, median, errors, invalid, pending }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Creates a result with the given DR value and scan state.
+    fn result(dr_value: Option<u8>, scanned: bool, invalid: bool) -> DRResult {
+        DRResult {
+            dr_value,
+            filename: "dr.txt".to_string(),
+            path: PathBuf::from("/music/dr.txt"),
+            scanned,
+            is_new: false,
+            marked: false,
+            invalid,
+        }
+    }
+
+    /// Creates scanned results with the given DR values.
+    fn results(values: &[u8]) -> Vec<DRResult> {
+        values.iter().map(|&dr| result(Some(dr), true, false)).collect()
+    }
+
+    /// Two tiers: "Low" below DR8 and "High" from DR8.
+    fn tiers() -> Vec<DrTier> {
+        vec![
+            DrTier { min_dr: 0, name: "Low".to_string(), color: (255, 0, 0) },
+            DrTier { min_dr: 8, name: "High".to_string(), color: (0, 255, 0) },
+        ]
+    }
+
+    #[test]
+    fn the_median_of_an_odd_count_is_the_middle_value() {
+        let statistics = Statistics::compute_with_tiers(&results(&[12, 4, 7]), &tiers());
+        assert_eq!(statistics.median, Some(7.0));
+        assert_eq!(statistics.mean, Some(23.0 / 3.0));
+    }
+
+    #[test]
+    fn the_median_of_an_even_count_is_the_mean_of_the_middle_values() {
+        let statistics = Statistics::compute_with_tiers(&results(&[12, 4, 7, 10]), &tiers());
+        assert_eq!(statistics.median, Some(8.5));
+    }
+
+    #[test]
+    fn values_are_counted_per_tier_and_histogram_bar() {
+        let statistics = Statistics::compute_with_tiers(&results(&[0, 7, 8, 25]), &tiers());
+        assert_eq!(statistics.tiers, [("Low".to_string(), 2), ("High".to_string(), 2)]);
+        assert_eq!((statistics.histogram[0], statistics.histogram[7], statistics.histogram[8]), (1, 1, 1));
+        assert_eq!(statistics.histogram[HISTOGRAM_MAX as usize], 1);
+    }
+
+    #[test]
+    fn logs_without_a_value_are_split_into_errors_invalid_and_pending() {
+        let statistics = Statistics::compute_with_tiers(&[
+            result(None, true, false),
+            result(None, true, true),
+            result(None, true, true),
+            result(None, false, false),
+            result(Some(9), true, false),
+        ], &tiers());
+        assert_eq!((statistics.errors, statistics.invalid, statistics.pending), (1, 2, 1));
+        assert_eq!(statistics.median, Some(9.0));
+        assert_eq!(statistics.tiers.iter().map(|(_, count)| count).sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn no_values_give_no_mean_or_median() {
+        let statistics = Statistics::compute_with_tiers(&[], &tiers());
+        assert_eq!((statistics.mean, statistics.median), (None, None));
+    }
+}
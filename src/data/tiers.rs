@@ -0,0 +1,201 @@
+use std::sync::{OnceLock, RwLock};
+
+use crate::utils::config::{load_key_file, save_key_file};
+use crate::utils::constants::{dr_tier_name, DR_COLORS};
+
+/// The file in the config directory that stores the DR tiers.
+const TIERS_FILE: &str = "tiers.ini";
+
+/// The tiers currently in use, loaded from the config directory on first use.
+static CURRENT_TIERS: OnceLock<RwLock<Vec<DrTier>>> = OnceLock::new();
+
+/// A named range of DR values shown in one color.
+///
+/// A tier starts at `min_dr` and reaches up to the next tier; the last tier has no upper bound.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrTier {
+    pub min_dr: u8,
+    pub name: String,
+    pub color: (u8, u8, u8),
+}
+
+/// A color ramp that can be applied to the tiers, from the lowest to the highest tier.
+pub struct Palette {
+    pub name: &'static str,
+    /// The colors the ramp passes through; tiers in between are interpolated.
+    pub stops: &'static [(u8, u8, u8)],
+}
+
+/// The preset palettes offered in the preferences.
+///
+/// Viridis and Cividis stay readable with red-green color blindness, and the high-contrast
+/// palette uses the Okabe-Ito colors.
+pub const PALETTES: [Palette; 4] = [
+    Palette { name: "Classic", stops: &[(255, 0, 0), (255, 72, 0), (255, 145, 0), (255, 217, 0), (217, 255, 0), (144, 255, 0), (72, 255, 0), (0, 255, 0)] },
+    Palette { name: "Viridis", stops: &[(68, 1, 84), (59, 82, 139), (33, 145, 140), (94, 201, 98), (253, 231, 37)] },
+    Palette { name: "Cividis", stops: &[(0, 32, 77), (65, 77, 107), (124, 123, 120), (188, 175, 111), (255, 234, 70)] },
+    Palette { name: "High Contrast", stops: &[(213, 94, 0), (230, 159, 0), (240, 228, 66), (86, 180, 233), (0, 114, 178)] },
+];
+
+impl Palette {
+
+    /// Returns `count` colors evenly spread over the ramp.
+    pub fn colors(&self, count: usize) -> Vec<(u8, u8, u8)> {
+        (0..count).map(|index| {
+            let position = if count > 1 { index as f64 / (count - 1) as f64 } else { 0.0 } * (self.stops.len() - 1) as f64;
+            let lower = position.floor() as usize;
+            let upper = (lower + 1).min(self.stops.len() - 1);
+            let fraction = position - lower as f64;
+            let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * fraction).round() as u8;
+            let (a, b) = (self.stops[lower], self.stops[upper]);
+            (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+        }).collect()
+    }
+}
+
+/// Returns the tiers listed in the README, colored with the classic ramp of `DR_COLORS`.
+///
+/// A new tier starts wherever the name or the color of the ramp changes, so every DR value
+/// keeps exactly the color it has in `DR_COLORS`.
+pub fn default_tiers() -> Vec<DrTier> {
+    let mut tiers: Vec<DrTier> = Vec::new();
+    for (dr, &color) in (0..).zip(DR_COLORS.iter()) {
+        if tiers.last().is_none_or(|tier| tier.name != dr_tier_name(dr) || tier.color != color) {
+            tiers.push(DrTier { min_dr: dr, name: dr_tier_name(dr).to_string(), color });
+        }
+    }
+    tiers
+}
+
+/// Sorts the tiers by their lowest DR value and makes the first tier start at DR0.
+///
+/// Tiers starting at the same DR value are merged into the first of them. An empty list
+/// is replaced by the default tiers.
+pub fn normalize_tiers(mut tiers: Vec<DrTier>) -> Vec<DrTier> {
+    tiers.sort_by_key(|tier| tier.min_dr);
+    tiers.dedup_by_key(|tier| tier.min_dr);
+    match tiers.first_mut() {
+        Some(first) => first.min_dr = 0,
+        None => return default_tiers(),
+    }
+    tiers
+}
+
+/// Returns a copy of the tiers currently in use.
+pub fn current_tiers() -> Vec<DrTier> {
+    tiers_lock().read().map(|tiers| tiers.clone()).unwrap_or_else(|_| default_tiers())
+}
+
+/// Replaces the tiers in use and saves them to the config directory.
+///
+/// The tiers are used even if they could not be saved; the error is returned for reporting.
+pub fn set_current_tiers(tiers: Vec<DrTier>) -> Result<(), String> {
+    let tiers = normalize_tiers(tiers);
+    let saved = save_tiers(&tiers);
+    if let Ok(mut current) = tiers_lock().write() {
+        *current = tiers;
+    }
+    saved
+}
+
+/// Returns the index of the tier a DR value belongs to among `tiers`.
+pub fn tier_index(tiers: &[DrTier], dr: u8) -> usize {
+    tiers.iter().rposition(|tier| tier.min_dr <= dr).unwrap_or(0)
+}
+
+/// Returns the index and the tier a DR value belongs to.
+///
+/// Only the matching tier is copied. The DR column, which looks up a tier for every row it
+/// binds, uses the classes and names cached by `load_dr_color_css` instead.
+pub fn tier_for(dr: u8) -> (usize, DrTier) {
+    let pick = |tiers: &[DrTier]| {
+        let index = tier_index(tiers, dr);
+        (index, tiers[index].clone())
+    };
+    match tiers_lock().read() {
+        Ok(tiers) => pick(&tiers),
+        Err(_) => pick(&default_tiers()),
+    }
+}
+
+/// Returns the color of the tier a DR value belongs to.
+pub fn tier_color(dr: u8) -> (u8, u8, u8) {
+    tier_for(dr).1.color
+}
+
+/// Formats a color as "#rrggbb".
+pub fn color_to_hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Parses a color written as "#rrggbb".
+pub fn color_from_hex(text: &str) -> Option<(u8, u8, u8)> {
+    let hex = text.trim().strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+    Some((channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+/// Returns the lock around the current tiers, loading them on first use.
+fn tiers_lock() -> &'static RwLock<Vec<DrTier>> {
+    CURRENT_TIERS.get_or_init(|| RwLock::new(load_tiers()))
+}
+
+/// Loads the tiers from the config directory, or returns the default tiers.
+///
+/// Each tier is stored in its own group with its lowest DR value, name and color.
+fn load_tiers() -> Vec<DrTier> {
+    let key_file = load_key_file(TIERS_FILE);
+    let tiers: Vec<DrTier> = key_file.groups().iter().filter_map(|group| {
+        Some(DrTier {
+            min_dr: u8::try_from(key_file.integer(group, "min_dr").ok()?).ok()?,
+            name: key_file.string(group, "name").ok()?.to_string(),
+            color: color_from_hex(&key_file.string(group, "color").ok()?)?,
+        })
+    }).collect();
+    normalize_tiers(tiers)
+}
+
+/// Saves the tiers to the config directory.
+fn save_tiers(tiers: &[DrTier]) -> Result<(), String> {
+    let key_file = load_key_file(TIERS_FILE);
+    for group in key_file.groups() {
+        let _ = key_file.remove_group(&group);
+    }
+    for (index, tier) in tiers.iter().enumerate() {
+        let group = format!("tier{}", index);
+        key_file.set_integer(&group, "min_dr", tier.min_dr as i32);
+        key_file.set_string(&group, "name", &tier.name);
+        key_file.set_string(&group, "color", &color_to_hex(tier.color));
+    }
+    save_key_file(TIERS_FILE, &key_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tiers_keep_the_classic_colors() {
+        let tiers = default_tiers();
+        for dr in 0..=u8::MAX {
+            let expected = DR_COLORS[(dr as usize).min(DR_COLORS.len() - 1)];
+            assert_eq!(tiers[tier_index(&tiers, dr)].color, expected, "DR{}", dr);
+            assert_eq!(tiers[tier_index(&tiers, dr)].name, dr_tier_name(dr), "DR{}", dr);
+        }
+    }
+
+    #[test]
+    fn normalized_tiers_start_at_zero() {
+        let tiers = normalize_tiers(vec![
+            DrTier { min_dr: 12, name: "High".to_string(), color: (0, 255, 0) },
+            DrTier { min_dr: 5, name: "Low".to_string(), color: (255, 0, 0) },
+            DrTier { min_dr: 12, name: "Duplicate".to_string(), color: (0, 0, 255) },
+        ]);
+        assert_eq!(tiers.iter().map(|tier| (tier.min_dr, tier.name.as_str())).collect::<Vec<_>>(), [(0, "Low"), (12, "High")]);
+        assert_eq!(tier_index(&tiers, 11), 0);
+        assert_eq!(tier_index(&tiers, 30), 1);
+    }
+}
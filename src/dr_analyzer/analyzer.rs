@@ -1,61 +1,139 @@
-use std::fs::read;
-use std::cmp::Ordering::{Greater, Less};
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::collections::{HashMap, HashSet};
+use std::mem::replace;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, mpsc::channel, Mutex};
+use std::sync::{Arc, Condvar, mpsc::channel, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
 use std::thread::{self, spawn};
+use std::time::Duration;
 
 use gio::ListStore;
-use glib::{clone, idle_add_local, MainContext};
+use glib::{timeout_add_local, MainContext};
 use glib::ControlFlow::{Break, Continue};
-use gtk4::{Button, ProgressBar, StringObject};
-use libadwaita::prelude::WidgetExt;
+use gtk4::{ProgressBar, StringObject};
+use libadwaita::prelude::{CastNone, ListModelExt, WidgetExt};
 
 use crate::data::app_state::{AppState, DRResult};
-use crate::file_manager::file_ops::find_log_files;
+use crate::file_manager::archive::read_log_bytes;
+use crate::file_manager::file_ops::{root_contains, walk_roots_parallel};
 use crate::utils::constants::get_dr_regex;
 
-/// Scans a directory for log files asynchronously, updates the UI with progress,
-/// and populates the list store with the results.
+/// How often the UI thread collects the results that arrived from the scan pipeline.
+const SCAN_REFRESH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shared signals that let the UI stop or pause a running scan.
+///
+/// The walker and analyzer threads check the control before each unit of work. While the
+/// scan is paused they block on a condition variable; once it is cancelled they stop.
+#[derive(Default)]
+pub struct ScanControl {
+    cancelled: AtomicBool,
+    paused: Mutex<bool>,
+    resumed: Condvar,
+}
+
+impl ScanControl {
+
+    /// Requests that the scan stops as soon as possible. Paused threads are woken up.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, SeqCst);
+        self.set_paused(false);
+    }
+
+    /// Returns `true` once `cancel` has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(SeqCst)
+    }
+
+    /// Pauses or resumes the scan.
+    pub fn set_paused(&self, paused: bool) {
+        *self.paused.lock().unwrap() = paused;
+        self.resumed.notify_all();
+    }
+
+    /// Returns `true` while the scan is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    /// Blocks the calling thread while the scan is paused.
+    ///
+    /// Returns `false` if the scan was cancelled and the caller should stop working.
+    pub fn wait_while_paused(&self) -> bool {
+        let mut paused = self.paused.lock().unwrap();
+        while *paused && !self.is_cancelled() {
+            paused = self.resumed.wait(paused).unwrap();
+        }
+        !self.is_cancelled()
+    }
+}
+
+/// Messages sent from the scan pipeline to the GTK UI thread.
+enum ScanEvent {
+    /// The directory walkers found another log file.
+    Discovered(PathBuf),
+    /// The directory walk is complete; no more files will be discovered.
+    WalkFinished,
+    /// A file has been analyzed.
+    Analyzed(DRResult),
+    /// All discovered files have been analyzed.
+    Finished,
+}
+
+/// Scans all scan roots for log files asynchronously and streams the results into the list store.
 ///
-/// This function spawns a primary worker thread to find files and then distributes
-/// the analysis of each file across a pool of secondary worker threads (sized to the number of CPU cores).
-/// Communication with the GTK UI thread is handled via MPSC channels and `glib::idle_add_local`.
-pub fn scan_directory(path: PathBuf, app_state: Arc<Mutex<AppState>>, progress_bar: ProgressBar, list_store:ListStore, scan_button: Button, clear_button: Button) {
-    let (progress_tx, progress_rx_inner) = channel::<(usize, usize)>();
-    let progress_rx = Arc::new(Mutex::new(progress_rx_inner));
-    let (results_tx, results_rx_inner) = channel::<Vec<DRResult>>();
-    let results_rx = Arc::new(Mutex::new(results_rx_inner));
+/// The scan runs as a pipeline:
+/// 1. A pool of walker threads traverses the roots in parallel (see `walk_roots_parallel`).
+/// 2. A coordinator thread removes duplicates and hands each file to the analyzers right away.
+/// 3. A pool of analyzer threads (sized to the number of CPU cores) extracts the DR values.
+///    Logs inside archives arrive with their contents, so each archive is only read once.
+///
+/// The UI thread drains the pipeline every `SCAN_REFRESH_INTERVAL` and merges each batch of
+/// results into `AppState`, so the first rows appear while the walk is still in progress.
+/// Only the rows of the batch are updated in the list (see `update_rows`), so a refresh takes
+/// as long for a large list as for a small one.
+/// The progress bar pulses until the total number of files is known.
+///
+/// Once the scan has finished, the rows that were listed before it started but were not found
+/// again beneath `roots` are removed. Rows of other roots and rows added while the scan was
+/// running (by watch mode or by adding roots) are kept.
+///
+/// The scan can be paused or cancelled through `control`, which must also be stored in
+/// `AppState::scan_control`. When the scan is cancelled, the results gathered so far are kept
+/// and every earlier row beneath `roots` that was not analyzed in this run is marked as
+/// `PENDING`. If the control is replaced or removed from the state (e.g. by clearing the
+/// list), the remaining results are discarded. `on_finished` is called on the UI thread once the scan has ended.
+pub fn scan_directory(roots: Vec<PathBuf>, control: Arc<ScanControl>, app_state: Arc<Mutex<AppState>>, progress_bar: ProgressBar, list_store:ListStore, on_finished: impl FnOnce() + 'static) {
+    let (event_tx, event_rx) = channel::<ScanEvent>();
+    let num_workers = thread::available_parallelism().map(|p| p.get()).unwrap_or(1).max(1);
+    let worker_control = control.clone();
+    let walked_roots = roots.clone();
+    let report_problem = app_state.lock().map(|state| state.report_problem.clone()).unwrap_or_else(|err| err.into_inner().report_problem.clone());
     spawn(move || {
-        let mut files = Vec::new();
-        if find_log_files(&path, &mut files).is_err() {
-            results_tx.send(Vec::new()).ok();
-            return;
-        }
-        let total_files = files.len();
-        if total_files == 0 {
-            progress_tx.send((0, 0)).ok();
-            results_tx.send(Vec::new()).expect("Failed to send empty results");
-            return;
-        }
-        let (file_tx, file_rx) = channel::<PathBuf>();
+        let control = worker_control;
+        let (file_tx, file_rx) = channel::<(PathBuf, Option<Vec<u8>>)>();
         let file_rx = Arc::new(Mutex::new(file_rx));
-        let (result_tx_worker, result_rx_worker) = channel::<DRResult>();
-        let num_cpus = thread::available_parallelism().map(|p| p.get()).unwrap_or(1);
-        let num_workers = num_cpus.max(1);
         let mut handles = Vec::with_capacity(num_workers);
         for _ in 0..num_workers {
             let file_rx_clone = Arc::clone(&file_rx);
-            let result_tx_worker_clone = result_tx_worker.clone();
+            let event_tx_clone = event_tx.clone();
+            let control_clone = Arc::clone(&control);
             handles.push(spawn(move || {
                 loop {
+                    if !control_clone.wait_while_paused() {
+                        break;
+                    }
                     let file_path_result = {
                         let rx = file_rx_clone.lock().unwrap();
                         rx.recv()
                     };
                     match file_path_result {
-                        Ok(file_path) => {
-                            let result = analyze_file(&file_path);
-                            if result_tx_worker_clone.send(result).is_err() {
+                        Ok((file_path, contents)) => {
+                            let result = match contents {
+                                Some(bytes) => analyze_content(&file_path, &bytes),
+                                None => analyze_file(&file_path),
+                            };
+                            if event_tx_clone.send(ScanEvent::Analyzed(result)).is_err() {
                                 break;
                             }
                         },
@@ -64,66 +142,170 @@ pub fn scan_directory(path: PathBuf, app_state: Arc<Mutex<AppState>>, progress_b
                 }
             }));
         }
-        for file_path in files {
-            file_tx.send(file_path).ok();
-        }
-        drop(file_tx);
-        let mut collected_results = Vec::with_capacity(total_files);
-        for i in 0..total_files {
-            if let Ok(result) = result_rx_worker.recv() {
-                collected_results.push(result);
-                progress_tx.send((i + 1, total_files)).ok();
-            } else {
-                eprintln!("Worker result channel closed unexpectedly.");
-                break;
+
+        // Walk the roots on a separate pool and forward every new file to the analyzers.
+        let (found_tx, found_rx) = channel::<(PathBuf, Option<Vec<u8>>)>();
+        let walker_control = Arc::clone(&control);
+        let walker = spawn(move || walk_roots_parallel(&roots, num_workers, &walker_control, found_tx, &report_problem));
+        let mut seen = HashSet::new();
+        for (file_path, contents) in found_rx {
+            if seen.insert(file_path.clone()) {
+                event_tx.send(ScanEvent::Discovered(file_path.clone())).ok();
+                file_tx.send((file_path, contents)).ok();
             }
         }
+        walker.join().expect("Walker thread panicked");
+        event_tx.send(ScanEvent::WalkFinished).ok();
+        drop(file_tx);
         for handle in handles {
             handle.join().expect("Worker thread panicked");
         }
-        drop(result_tx_worker);
-        let results = collected_results;
-        results_tx.send(results).ok();
+        event_tx.send(ScanEvent::Finished).ok();
     });
-    idle_add_local(clone!(@strong progress_bar, @strong progress_rx => move || {
-        if let Ok((current, total)) = progress_rx.lock().unwrap().try_recv() {
-            if total > 0 {
-                progress_bar.set_fraction(current as f64 / total as f64);
+
+    // Remember where every known path is stored so incoming results can replace pending rows.
+    let mut index: HashMap<PathBuf, usize> = app_state.lock()
+        .map(|state| state.results.iter().enumerate().map(|(i, result)| (result.path.clone(), i)).collect())
+        .unwrap_or_default();
+
+    // Only rows that existed before the scan can be stale; rows added while it runs are kept.
+    let previous_paths: HashSet<PathBuf> = index.keys().cloned().collect();
+    let mut scanned_paths = HashSet::new();
+    let mut discovered = Vec::new();
+    let (mut analyzed, mut walk_finished) = (0usize, false);
+    let mut on_finished = Some(on_finished);
+    timeout_add_local(SCAN_REFRESH_INTERVAL, move || {
+        let mut batch = Vec::new();
+        let mut finished = false;
+        while let Ok(event) = event_rx.try_recv() {
+            match event {
+                ScanEvent::Discovered(path) => discovered.push(path),
+                ScanEvent::WalkFinished => walk_finished = true,
+                ScanEvent::Analyzed(result) => batch.push(result),
+                ScanEvent::Finished => {
+                    finished = true;
+                    break;
+                }
             }
-            Continue
-        } else {
-            Break
-        }
-    }));
-    let results_rx_clone = results_rx.clone();
-    idle_add_local(clone!(@strong list_store, @strong app_state, @strong progress_bar, @strong scan_button, @strong clear_button => move || {
-        if let Ok(results) = results_rx_clone.lock().unwrap().try_recv() {
-            if let Ok(mut state) = app_state.lock() {
-                state.results = results;
-                update_ui(&list_store, &state.results);
+        }
+        analyzed += batch.len();
+        let mut abandoned = false;
+        if let Ok(mut state) = app_state.lock() {
+
+            // The scan was superseded (e.g. the list was cleared); stop without touching the results.
+            if !state.scan_control.as_ref().is_some_and(|current| Arc::ptr_eq(current, &control)) {
+                control.cancel();
+                abandoned = true;
+            } else {
+
+                // The previous and the new state of every result that changed, for `update_rows`.
+                let (mut removed, mut changed) = (Vec::new(), Vec::new());
+                for result in batch {
+                    scanned_paths.insert(result.path.clone());
+
+                    // Rows may have been removed or added since the scan started; rebuild the index if it is stale.
+                    if index.get(&result.path).is_some_and(|&i| state.results.get(i).is_none_or(|r| r.path != result.path)) {
+                        index = state.results.iter().enumerate().map(|(i, r)| (r.path.clone(), i)).collect();
+                    }
+                    match index.get(&result.path) {
+                        Some(&i) => {
+                            let marked = state.results[i].marked;
+                            removed.push(replace(&mut state.results[i], DRResult { marked, ..result }));
+                            changed.push(state.results[i].clone());
+                        }
+                        None => {
+                            index.insert(result.path.clone(), state.results.len());
+                            changed.push(result.clone());
+                            state.results.push(result);
+                        }
+                    }
+                }
+                if finished && control.is_cancelled() {
+
+                    // Keep what was analyzed; the other rows of this scan go back to the pending state.
+                    let unscanned = |result: &&mut DRResult| {
+                        !scanned_paths.contains(&result.path)
+                            && previous_paths.contains(&result.path)
+                            && walked_roots.iter().any(|root| root_contains(root, &result.path))
+                    };
+                    for result in state.results.iter_mut().filter(unscanned) {
+                        removed.push(result.clone());
+                        result.dr_value = None;
+                        result.scanned = false;
+                        changed.push(result.clone());
+                    }
+                    let known: HashSet<PathBuf> = state.results.iter().map(|result| result.path.clone()).collect();
+                    for path in discovered.drain(..).filter(|path| !known.contains(path)) {
+                        let pending = DRResult {
+                            filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+                            path,
+                            dr_value: None,
+                            scanned: false,
+                            is_new: false,
+                            marked: false,
+                            invalid: false,
+                        };
+                        changed.push(pending.clone());
+                        state.results.push(pending);
+                    }
+                } else if finished {
+
+                    // Drop entries whose files no longer exist beneath the roots of this scan.
+                    removed.extend(state.results.extract_if(.., |result| {
+                        !scanned_paths.contains(&result.path)
+                            && previous_paths.contains(&result.path)
+                            && walked_roots.iter().any(|root| root_contains(root, &result.path))
+                    }));
+                }
+                if finished {
+                    state.scan_control = None;
+                }
+                update_rows(&list_store, &removed, &changed);
             }
+        }
+        if finished || abandoned {
             progress_bar.set_visible(false);
-            scan_button.set_sensitive(true);
-            clear_button.set_sensitive(true);
-            Break
+            progress_bar.set_show_text(false);
+            if let Some(on_finished) = on_finished.take() {
+                on_finished();
+            }
+            return Break;
+        }
+        if control.is_paused() {
+            progress_bar.set_show_text(true);
+            progress_bar.set_text(Some("Paused"));
         } else {
-            Continue
+            progress_bar.set_show_text(false);
+            if walk_finished && !discovered.is_empty() {
+                progress_bar.set_fraction(analyzed as f64 / discovered.len() as f64);
+            } else {
+                progress_bar.pulse();
+            }
         }
-    }));
+        Continue
+    });
 }
 
 /// Analyzes a single log file to extract its DR (Dynamic Range) value.
 ///
 /// It reads the file content and uses a regular expression to find the DR value.
+/// Virtual paths pointing into an archive are read in memory without extracting them.
 /// If the file cannot be read or the value cannot be parsed, it returns a `DRResult`
-/// indicating an error.
+/// indicating an error. Logs that cannot be read or contain no DR line at all are
+/// flagged as invalid, unlike logs that report `DRERR`.
 pub fn analyze_file(path: &Path) -> DRResult {
-    let content = match read(path) {
-        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
-        Err(_) => return create_error_result(path),
-    };
-    let dr_value = get_dr_regex()
-        .captures(&content)
+    match read_log_bytes(path) {
+        Ok(bytes) => analyze_content(path, &bytes),
+        Err(_) => create_error_result(path),
+    }
+}
+
+/// Extracts the DR value from the contents of a log that has already been read.
+pub fn analyze_content(path: &Path, bytes: &[u8]) -> DRResult {
+    let content = String::from_utf8_lossy(bytes);
+    let captures = get_dr_regex().captures(&content);
+    let invalid = captures.is_none();
+    let dr_value = captures
         .and_then(|caps| {
             caps.get(1)
                 .or_else(|| caps.get(2))
@@ -142,61 +324,364 @@ pub fn analyze_file(path: &Path) -> DRResult {
         path: path.to_path_buf(),
         dr_value,
         scanned: true,
+        is_new: false,
+        marked: false,
+        invalid,
     }
 }
 
 /// Creates a `DRResult` that represents a scan error for a given file path.
 ///
-/// The `scanned` flag is set to `true` to distinguish it from a pending file.
+/// The `scanned` flag is set to `true` to distinguish it from a pending file, and the
+/// result is flagged as invalid.
 pub fn create_error_result(path: &Path) -> DRResult {
     DRResult {
         filename: path.file_name().unwrap().to_string_lossy().into_owned(),
         path: path.to_path_buf(),
         dr_value: None,
         scanned: true,
+        is_new: false,
+        marked: false,
+        invalid: true,
     }
 }
 
-/// Clears and repopulates the UI's list store with sorted scan results.
+/// Brings the UI's list store in line with the sorted scan results.
 ///
 /// This function ensures that the UI update occurs on the main GTK thread.
 /// The results are sorted with the following priority:
 /// 1. Descending DR value (highest first).
 /// 2. Alphabetical by path for files with the same DR value.
-/// 3. Files with errors (`ERR`) are grouped after successfully scanned files.
+/// 3. Files with errors (`ERR` or `INVALID`) are grouped after successfully scanned files.
 /// 4. Unscanned files (`PENDING`) are shown last.
+///
+/// Instead of rebuilding the store, the sorted rows are merged with the rows already shown
+/// (which are in the same order), and only the rows that were added, removed or changed are
+/// spliced. Unchanged rows keep their objects, so the selection and scroll position survive.
+///
+/// This sorts and formats every result, so it is meant for changes that touch many rows at
+/// once. When only a few known results changed, `update_rows` is much cheaper.
 pub fn update_ui(list_store: &ListStore, results: &[DRResult]) {
-    let results = results.to_vec();
+    let mut sorted_results: Vec<&DRResult> = results.iter().collect();
+    sorted_results.sort_by(|a, b| compare_order((a.dr_value, a.scanned, &a.path), (b.dr_value, b.scanned, &b.path)));
+    let rows: Vec<String> = sorted_results.into_iter().map(row_text).collect();
     let list_store = list_store.clone();
     MainContext::default().invoke_local(move || {
-        list_store.remove_all();
-        let mut sorted_results = results;
-        sorted_results.sort_by(|a, b| {
-            match (a.dr_value, b.dr_value) {
-                (Some(a_val), Some(b_val)) => b_val.cmp(&a_val)
-                    .then_with(|| a.path.cmp(&b.path)),
-                (Some(_), None) => Less,
-                (None, Some(_)) => Greater,
-                (None, None) => match (a.scanned, b.scanned) {
-                    (true, true) | (false, false) => a.path.cmp(&b.path),
-                    (true, false) => Greater,
-                    (false, true) => Less,
-                }
-            }
-        });
-        for result in sorted_results {
-            let dr_text = match (result.dr_value, result.scanned) {
-                (Some(dr), _) => dr.to_string(),
-                (None, true) => "ERR".to_string(),
-                (None, false) => "PENDING".to_string(),
-            };
-            let text = format!(
-                "{}	{}	{}",
-                result.filename,
-                result.path.to_string_lossy(),
-                dr_text
-            );
-            list_store.append(&StringObject::new(&text));
+        let old_rows: Vec<String> = (0..list_store.n_items())
+            .filter_map(|i| list_store.item(i).and_downcast::<StringObject>())
+            .map(|row| row.string().to_string())
+            .collect();
+
+        let changes = row_changes(&old_rows, &rows);
+
+        // Apply the runs from the end, so that the positions of the earlier runs stay valid.
+        for (position, n_removed, additions) in changes.into_iter().rev() {
+            let additions: Vec<StringObject> = additions.into_iter().map(StringObject::new).collect();
+            list_store.splice(position as u32, n_removed as u32, &additions);
         }
     });
-}
\ No newline at end of file
+}
+
+/// Updates the rows of a few results in the list store, which is in the order of `update_ui`.
+///
+/// The rows of the `removed` results are taken out, and the `changed` results are inserted at
+/// their sorted position, replacing the row of the same log. A result whose DR value changed
+/// must also be passed in `removed`, with its previous value, so that its old row is found.
+/// Every row is located by a binary search, so the cost depends on the number of changed
+/// results rather than on the size of the list, and all other rows keep their objects.
+pub fn update_rows(list_store: &ListStore, removed: &[DRResult], changed: &[DRResult]) {
+    if removed.is_empty() && changed.is_empty() {
+        return;
+    }
+    let removed: Vec<String> = removed.iter().map(row_text).collect();
+    let changed: Vec<String> = changed.iter().map(row_text).collect();
+    let mut list_store = list_store.clone();
+    MainContext::default().invoke_local(move || apply_row_updates(&mut list_store, &removed, &changed));
+}
+
+/// The sorted rows of the results list, as changed by `update_rows`.
+trait SortedRows {
+    fn row_count(&self) -> usize;
+
+    /// Returns the text of the row at `position`.
+    fn row(&self, position: usize) -> String;
+
+    /// Removes `n_removed` rows at `position` and inserts `row` there, if given.
+    fn splice_row(&mut self, position: usize, n_removed: usize, row: Option<&str>);
+}
+
+impl SortedRows for ListStore {
+    fn row_count(&self) -> usize {
+        self.n_items() as usize
+    }
+
+    fn row(&self, position: usize) -> String {
+        self.item(position as u32).and_downcast::<StringObject>().map(|row| row.string().to_string()).unwrap_or_default()
+    }
+
+    fn splice_row(&mut self, position: usize, n_removed: usize, row: Option<&str>) {
+        let additions: Vec<StringObject> = row.into_iter().map(StringObject::new).collect();
+        self.splice(position as u32, n_removed as u32, &additions);
+    }
+}
+
+/// Finds the row with the order key `key` by a binary search.
+///
+/// Returns its position, or the position where it would have to be inserted.
+fn find_row(rows: &impl SortedRows, key: (Option<u8>, bool, &Path)) -> Result<usize, usize> {
+    let (mut low, mut high) = (0, rows.row_count());
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match compare_order(row_order_key(&rows.row(middle)), key) {
+            Less => low = middle + 1,
+            Greater => high = middle,
+            Equal => return Ok(middle),
+        }
+    }
+    Err(low)
+}
+
+/// Removes the `removed` rows and inserts the `changed` rows at their sorted positions.
+///
+/// A removed row with the same order key as a changed row is replaced by it in place instead.
+fn apply_row_updates(rows: &mut impl SortedRows, removed: &[String], changed: &[String]) {
+    let changed_keys: HashSet<(Option<u8>, bool, &Path)> = changed.iter().map(|row| row_order_key(row)).collect();
+    for row in removed.iter().filter(|row| !changed_keys.contains(&row_order_key(row))) {
+        if let Ok(position) = find_row(rows, row_order_key(row)) {
+            rows.splice_row(position, 1, None);
+        }
+    }
+    for row in changed {
+        match find_row(rows, row_order_key(row)) {
+            Ok(position) if rows.row(position) == *row => {}
+            Ok(position) => rows.splice_row(position, 1, Some(row)),
+            Err(position) => rows.splice_row(position, 0, Some(row)),
+        }
+    }
+}
+
+/// Collects the runs of rows that differ between two lists sorted in the order of `update_ui`.
+///
+/// Both lists are walked in step. Each run is returned as (position in the old rows, number
+/// of old rows removed, new rows added), in ascending order of position. Rows that are equal
+/// in both lists are not part of any run, so an unchanged list yields no runs at all.
+fn row_changes<'a>(old_rows: &[String], rows: &'a [String]) -> Vec<(usize, usize, Vec<&'a str>)> {
+    let mut changes: Vec<(usize, usize, Vec<&str>)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_rows.len() || j < rows.len() {
+        let order = match (old_rows.get(i), rows.get(j)) {
+            (Some(old), Some(new)) => compare_order(row_order_key(old), row_order_key(new)),
+            (Some(_), None) => Less,
+            _ => Greater,
+        };
+        let (removed, added) = match order {
+            Equal if old_rows[i] == rows[j] => {
+                (i, j) = (i + 1, j + 1);
+                continue;
+            }
+            Equal => (1, Some(rows[j].as_str())),
+            Less => (1, None),
+            Greater => (0, Some(rows[j].as_str())),
+        };
+        match changes.last_mut() {
+            Some((position, n_removed, _)) if *position + *n_removed == i => *n_removed += removed,
+            _ => changes.push((i, removed, Vec::new())),
+        }
+        if let (Some(added), Some((_, _, additions))) = (added, changes.last_mut()) {
+            additions.push(added);
+        }
+        i += removed;
+        j += added.is_some() as usize;
+    }
+    changes
+}
+
+/// Formats a result as a row of the list store: "filename\tpath\tdr\tnew\tmarked".
+fn row_text(result: &DRResult) -> String {
+    let dr_text = match (result.dr_value, result.scanned) {
+        (Some(dr), _) => dr.to_string(),
+        (None, true) if result.invalid => "INVALID".to_string(),
+        (None, true) => "ERR".to_string(),
+        (None, false) => "PENDING".to_string(),
+    };
+    format!(
+        "{}	{}	{}	{}	{}",
+        result.filename,
+        result.path.to_string_lossy(),
+        dr_text,
+        if result.is_new { "new" } else { "" },
+        if result.marked { "marked" } else { "" }
+    )
+}
+
+/// Reads the DR value, the scanned flag and the path of a row, as used by `compare_order`.
+fn row_order_key(text: &str) -> (Option<u8>, bool, &Path) {
+    let mut fields = text.split('\t').skip(1);
+    let path = Path::new(fields.next().unwrap_or(""));
+    let dr = fields.next().unwrap_or("PENDING");
+    (dr.parse::<u8>().ok(), dr != "PENDING", path)
+}
+
+/// The order of the results list; see `update_ui`.
+fn compare_order(a: (Option<u8>, bool, &Path), b: (Option<u8>, bool, &Path)) -> Ordering {
+    match (a.0, b.0) {
+        (Some(a_val), Some(b_val)) => b_val.cmp(&a_val)
+            .then_with(|| a.2.cmp(b.2)),
+        (Some(_), None) => Less,
+        (None, Some(_)) => Greater,
+        (None, None) => match (a.1, b.1) {
+            (true, true) | (false, false) => a.2.cmp(b.2),
+            (true, false) => Greater,
+            (false, true) => Less,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a scanned result with the given DR value.
+    fn result(path: &str, dr_value: Option<u8>) -> DRResult {
+        DRResult {
+            dr_value,
+            filename: Path::new(path).file_name().unwrap().to_string_lossy().into_owned(),
+            path: PathBuf::from(path),
+            scanned: true,
+            is_new: false,
+            marked: false,
+            invalid: false,
+        }
+    }
+
+    /// Formats the results as the rows of the list store, in the order of `update_ui`.
+    fn rows(results: &[DRResult]) -> Vec<String> {
+        let mut sorted = results.to_vec();
+        sorted.sort_by(|a, b| compare_order((a.dr_value, a.scanned, &a.path), (b.dr_value, b.scanned, &b.path)));
+        sorted.iter().map(row_text).collect()
+    }
+
+    /// Applies the runs to the old rows like `update_ui` applies them to the list store.
+    fn apply(old_rows: &[String], changes: Vec<(usize, usize, Vec<&str>)>) -> Vec<String> {
+        let mut applied = old_rows.to_vec();
+        for (position, n_removed, additions) in changes.into_iter().rev() {
+            applied.splice(position..position + n_removed, additions.into_iter().map(str::to_string));
+        }
+        applied
+    }
+
+    #[test]
+    fn unchanged_rows_produce_no_changes() {
+        let old_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", None)]);
+        assert!(row_changes(&old_rows, &old_rows).is_empty());
+    }
+
+    #[test]
+    fn inserted_row_is_spliced_in() {
+        let old_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10))]);
+        let new_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(11))]);
+        let changes = row_changes(&old_rows, &new_rows);
+        assert_eq!(changes, [(1, 0, vec![new_rows[1].as_str()])]);
+        assert_eq!(apply(&old_rows, changes), new_rows);
+    }
+
+    #[test]
+    fn removed_row_is_spliced_out() {
+        let old_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(8))]);
+        let new_rows = rows(&[result("/a.log", Some(12)), result("/c.log", Some(8))]);
+        let changes = row_changes(&old_rows, &new_rows);
+        assert_eq!(changes, [(1, 1, vec![])]);
+        assert_eq!(apply(&old_rows, changes), new_rows);
+    }
+
+    #[test]
+    fn changed_dr_moves_only_that_row() {
+        let old_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(8))]);
+        let new_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(13))]);
+        let changes = row_changes(&old_rows, &new_rows);
+        assert_eq!(changes, [(0, 0, vec![new_rows[0].as_str()]), (2, 1, vec![])]);
+        assert_eq!(apply(&old_rows, changes), new_rows);
+    }
+
+    #[test]
+    fn changed_badge_replaces_the_row_in_place() {
+        let mut marked = result("/b.log", Some(10));
+        marked.marked = true;
+        let old_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(8))]);
+        let new_rows = rows(&[result("/a.log", Some(12)), marked, result("/c.log", Some(8))]);
+        let changes = row_changes(&old_rows, &new_rows);
+        assert_eq!(changes, [(1, 1, vec![new_rows[1].as_str()])]);
+        assert_eq!(apply(&old_rows, changes), new_rows);
+    }
+
+    #[test]
+    fn one_changed_row_in_a_large_list_is_moved_alone() {
+        let mut results: Vec<DRResult> = (0..100_000)
+            .map(|index| result(&format!("/music/user{}/album {}/dr.txt", index % 100, index), Some((index % 13) as u8 + 4)))
+            .collect();
+        let old_rows = rows(&results);
+        results[4_242].dr_value = Some(20);
+        let new_rows = rows(&results);
+        let changes = row_changes(&old_rows, &new_rows);
+        assert_eq!(changes.len(), 2); // Removed from its old position, inserted at the top.
+        assert_eq!(changes.iter().map(|(_, removed, added)| removed + added.len()).sum::<usize>(), 2);
+        assert_eq!(apply(&old_rows, changes), new_rows);
+    }
+
+    impl SortedRows for Vec<String> {
+        fn row_count(&self) -> usize {
+            self.len()
+        }
+
+        fn row(&self, position: usize) -> String {
+            self[position].clone()
+        }
+
+        fn splice_row(&mut self, position: usize, n_removed: usize, row: Option<&str>) {
+            self.splice(position..position + n_removed, row.map(str::to_string));
+        }
+    }
+
+    #[test]
+    fn paths_are_ordered_by_component() {
+        let shown = rows(&[result("/a-b/x.log", Some(10)), result("/a/b/x.log", Some(10))]);
+        assert!(shown[0].contains("/a/b/x.log"));
+    }
+
+    #[test]
+    fn updated_rows_match_a_full_sort() {
+        let mut pending = result("/e.log", None);
+        pending.scanned = false;
+        let old = vec![result("/a.log", Some(12)), result("/b.log", Some(10)), result("/c.log", Some(8)), result("/d.log", None), pending.clone()];
+        let mut shown = rows(&old);
+
+        // Mark one row, change the DR of another, scan the pending one, add one and remove one.
+        let mut marked = old[1].clone();
+        marked.marked = true;
+        let changed = [marked, result("/c.log", Some(14)), result("/e.log", Some(9)), result("/f.log", Some(10))];
+        let removed = [old[2].clone(), pending, old[3].clone()];
+        apply_row_updates(&mut shown, &removed.iter().map(row_text).collect::<Vec<_>>(), &changed.iter().map(row_text).collect::<Vec<_>>());
+
+        let mut expected = vec![old[0].clone()];
+        expected.extend(changed);
+        assert_eq!(shown, rows(&expected));
+    }
+
+    #[test]
+    fn an_unchanged_row_is_not_replaced() {
+        let mut shown = rows(&[result("/a.log", Some(12)), result("/b.log", Some(10))]);
+        let before = shown.clone();
+        let unchanged = [row_text(&result("/b.log", Some(10)))];
+        apply_row_updates(&mut shown, &unchanged, &unchanged);
+        assert_eq!(shown, before);
+    }
+
+    #[test]
+    fn pending_row_becomes_scanned() {
+        let mut pending = result("/b.log", None);
+        pending.scanned = false;
+        let old_rows = rows(&[result("/a.log", Some(12)), pending]);
+        let new_rows = rows(&[result("/a.log", Some(12)), result("/b.log", Some(14))]);
+        assert_eq!(apply(&old_rows, row_changes(&old_rows, &new_rows)), new_rows);
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::dr_analyzer::log_parser::TrackEntry;
+
+/// Matches the track number at the start of a title, e.g. "01-Title", "1. Title" or "A1 Title".
+static TRACK_NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// A track of the left log next to the same track of the right log.
+///
+/// Either side is `None` if the track only appears in one of the logs.
+#[derive(Clone, Debug)]
+pub struct TrackPair {
+    pub left: Option<TrackEntry>,
+    pub right: Option<TrackEntry>,
+}
+
+impl TrackPair {
+
+    /// Returns how much higher the DR of the right track is, if both were measured.
+    pub fn dr_delta(&self) -> Option<i16> {
+        Some(self.right.as_ref()?.dr? as i16 - self.left.as_ref()?.dr? as i16)
+    }
+
+    /// Returns how much higher the peak of the right track is, in dB.
+    pub fn peak_delta(&self) -> Option<f64> {
+        Some(parse_db(&self.right.as_ref()?.peak)? - parse_db(&self.left.as_ref()?.peak)?)
+    }
+
+    /// Returns how much higher the RMS of the right track is, in dB.
+    pub fn rms_delta(&self) -> Option<f64> {
+        Some(parse_db(&self.right.as_ref()?.rms)? - parse_db(&self.left.as_ref()?.rms)?)
+    }
+}
+
+/// Parses a level such as "-0.10 dB" or "-14,42 dB". Silence ("-inf dB") yields `None`.
+pub fn parse_db(text: &str) -> Option<f64> {
+    text.trim_end_matches("dB").trim().replace(',', ".").parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+/// Splits a track title into its track number and the title without the number.
+fn split_track_number(title: &str) -> (Option<u32>, &str) {
+    let number = TRACK_NUMBER_REGEX.get_or_init(|| Regex::new(r"^\s*[A-Za-z]?(\d{1,3})(?:\s*[-._)]\s*|\s+)").unwrap());
+    match number.captures(title) {
+        Some(caps) => (caps[1].parse().ok(), &title[caps[0].len()..]),
+        None => (None, title),
+    }
+}
+
+/// Reduces a title to lowercase letters and digits, so that different spellings still match.
+fn normalized_title(title: &str) -> String {
+    split_track_number(title).1.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Aligns the per-track tables of two logs.
+///
+/// Tracks are paired by their track number if both titles carry one, and by their normalized
+/// title otherwise. The result follows the order of the left log; tracks that only appear in
+/// the right log are inserted after the track that precedes them in the right log.
+pub fn align_tracks(left: &[TrackEntry], right: &[TrackEntry]) -> Vec<TrackPair> {
+    let mut unmatched_right: Vec<Option<&TrackEntry>> = right.iter().map(Some).collect();
+    let mut pairs: Vec<(TrackPair, Option<usize>)> = Vec::new();
+    for left_track in left {
+        let (left_number, _) = split_track_number(&left_track.title);
+        let left_title = normalized_title(&left_track.title);
+        let index = unmatched_right.iter().position(|candidate| candidate.is_some_and(|right_track| {
+            match (left_number, split_track_number(&right_track.title).0) {
+                (Some(a), Some(b)) => a == b,
+                _ => !left_title.is_empty() && normalized_title(&right_track.title) == left_title,
+            }
+        }));
+        let right_track = index.and_then(|index| unmatched_right[index].take()).cloned();
+        pairs.push((TrackPair { left: Some(left_track.clone()), right: right_track }, index));
+    }
+
+    // Insert the remaining right tracks after the pair that precedes them in the right log.
+    for (index, right_track) in unmatched_right.into_iter().enumerate() {
+        let Some(right_track) = right_track else {
+            continue;
+        };
+        let position = pairs.iter().rposition(|(_, matched)| matched.is_some_and(|matched| matched < index)).map_or(0, |position| position + 1);
+        pairs.insert(position, (TrackPair { left: None, right: Some(right_track.clone()) }, Some(index)));
+    }
+    pairs.into_iter().map(|(pair, _)| pair).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a track with the given title and DR value.
+    fn track(title: &str, dr: u8) -> TrackEntry {
+        TrackEntry {
+            line: 0,
+            dr: Some(dr),
+            peak: "-0.10 dB".to_string(),
+            rms: "-12.00 dB".to_string(),
+            duration: "3:00".to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    /// Returns the titles of the aligned pairs, with "-" for a missing side.
+    fn titles(pairs: &[TrackPair]) -> Vec<(&str, &str)> {
+        fn title(track: &Option<TrackEntry>) -> &str {
+            track.as_ref().map_or("-", |track| track.title.as_str())
+        }
+        pairs.iter().map(|pair| (title(&pair.left), title(&pair.right))).collect()
+    }
+
+    #[test]
+    fn tracks_are_paired_by_track_number() {
+        let left = [track("01-Intro", 8), track("02-Song", 9)];
+        let right = [track("2. Song (Remastered)", 12), track("1. Intro", 10)];
+        let pairs = align_tracks(&left, &right);
+        assert_eq!(titles(&pairs), [("01-Intro", "1. Intro"), ("02-Song", "2. Song (Remastered)")]);
+        assert_eq!(pairs[1].dr_delta(), Some(3));
+    }
+
+    #[test]
+    fn tracks_without_numbers_are_paired_by_normalized_title() {
+        let left = [track("Intro", 8), track("Outro", 9)];
+        let right = [track("outro", 10), track("INTRO!", 11)];
+        assert_eq!(titles(&align_tracks(&left, &right)), [("Intro", "INTRO!"), ("Outro", "outro")]);
+    }
+
+    #[test]
+    fn right_only_tracks_follow_their_predecessor_in_the_right_log() {
+        let left = [track("01 A", 8), track("03 C", 8)];
+        let right = [track("00 Hidden", 9), track("01 A", 9), track("02 B", 9), track("03 C", 9), track("04 D", 9)];
+        assert_eq!(titles(&align_tracks(&left, &right)), [
+            ("-", "00 Hidden"),
+            ("01 A", "01 A"),
+            ("-", "02 B"),
+            ("03 C", "03 C"),
+            ("-", "04 D"),
+        ]);
+    }
+
+    #[test]
+    fn track_numbers_are_split_from_titles() {
+        assert_eq!(split_track_number("01-Title"), (Some(1), "Title"));
+        assert_eq!(split_track_number("A1 Title"), (Some(1), "Title"));
+        assert_eq!(split_track_number("1999"), (None, "1999"));
+    }
+
+    #[test]
+    fn levels_are_parsed_with_either_decimal_separator() {
+        assert_eq!(parse_db("-0.10 dB"), Some(-0.10));
+        assert_eq!(parse_db("-14,42 dB"), Some(-14.42));
+        assert_eq!(parse_db("+0.00 dB"), Some(0.0));
+        assert_eq!(parse_db("-inf dB"), None);
+    }
+}
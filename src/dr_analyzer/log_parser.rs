@@ -0,0 +1,176 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::file_manager::archive::read_log_bytes;
+
+/// Album information extracted from a DR log.
+///
+/// All fields are optional, since the log formats differ between tools and
+/// many logs found in the wild are incomplete.
+#[derive(Clone, Debug, Default)]
+pub struct LogMetadata {
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub year: Option<String>,
+}
+
+/// A row of the per-track table in a DR log.
+#[derive(Clone, Debug)]
+pub struct TrackEntry {
+    /// The index of the line in the log the track was read from.
+    pub line: usize,
+    /// The DR value of the track, or `None` if the meter reported `DRERR`.
+    pub dr: Option<u8>,
+    pub peak: String,
+    pub rms: String,
+    pub duration: String,
+    pub title: String,
+}
+
+/// Matches a track line, e.g. "DR12      -0.10 dB   -14.42 dB     4:32 01-Title".
+static TRACK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Matches the foobar2000 header line, e.g. "Analyzed: Artist / Album".
+static ANALYZED_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Matches "Key: value" tag lines as written by MAAT DROffline and tagged logs.
+static TAG_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Matches a four-digit year in parentheses or brackets, e.g. "(1973)" or "[1973]".
+static YEAR_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Matches album folder names of the form "Artist - Album".
+static FOLDER_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Reads a log (from disk or from inside an archive) as text.
+pub fn read_log_text(path: &Path) -> Option<String> {
+    read_log_bytes(path).ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Extracts artist, album and year from the text of a DR log.
+///
+/// The following sources are tried in order:
+/// - foobar2000 header: "Analyzed: Artist / Album" (or the Russian "Анализ:")
+/// - tag lines: "Artist:", "Album:", "Year:"/"Date:" (and their Russian equivalents)
+/// - a year in parentheses or brackets after the album title
+pub fn parse_metadata(content: &str) -> LogMetadata {
+    let analyzed = ANALYZED_REGEX.get_or_init(|| {
+        Regex::new(r"(?m)^\s*(?:Analyzed|Анализ|Проанализировано):\s*(.+?)\s+/\s+(.+?)\s*$").unwrap()
+    });
+    let tag = TAG_REGEX.get_or_init(|| {
+        Regex::new(r"(?mi)^\s*(artist|album artist|album|year|date|исполнитель|альбом|год)\s*:\s*(.+?)\s*$").unwrap()
+    });
+    let mut metadata = LogMetadata::default();
+    if let Some(caps) = analyzed.captures(content) {
+        metadata.artist = Some(caps[1].to_string());
+        metadata.album = Some(caps[2].to_string());
+    }
+    for caps in tag.captures_iter(content) {
+        let value = caps[2].to_string();
+        match caps[1].to_lowercase().as_str() {
+            "artist" | "album artist" | "исполнитель" => { metadata.artist.get_or_insert(value); }
+            "album" | "альбом" => { metadata.album.get_or_insert(value); }
+            "year" | "date" | "год" => { metadata.year.get_or_insert(value.chars().take(4).collect()); }
+            _ => {}
+        }
+    }
+
+    // Many logs only carry the year as part of the album title, e.g. "Album (1973)".
+    if let Some(album) = metadata.album.take() {
+        let year = YEAR_REGEX.get_or_init(|| Regex::new(r"\s*[(\[]((?:19|20)\d{2})[)\]]\s*").unwrap());
+        if let Some(caps) = year.captures(&album) {
+            metadata.year.get_or_insert(caps[1].to_string());
+        }
+        let stripped = year.replace(&album, " ").trim().to_string();
+        metadata.album = Some(if stripped.is_empty() { album } else { stripped });
+    }
+    metadata
+}
+
+/// Extracts the per-track table from the text of a DR log.
+///
+/// Both foobar2000 and TT DR Offline Meter logs list one track per line with its DR, peak,
+/// RMS, duration and title. Logs without such a table yield an empty list.
+pub fn parse_tracks(content: &str) -> Vec<TrackEntry> {
+    let track = TRACK_REGEX.get_or_init(|| {
+        Regex::new(r"^\s*DR(\d+|ERR)\s+([-+]?(?:\d+(?:[.,]\d+)?|inf)\s*dB)\s+([-+]?(?:\d+(?:[.,]\d+)?|inf)\s*dB)\s+(\d+:\d{2}(?::\d{2})?)\s+(.+?)\s*$").unwrap()
+    });
+    content.lines().enumerate().filter_map(|(line, text)| {
+        track.captures(text).map(|caps| TrackEntry {
+            line,
+            dr: caps[1].parse::<u8>().ok(),
+            peak: caps[2].to_string(),
+            rms: caps[3].to_string(),
+            duration: caps[4].to_string(),
+            title: caps[5].to_string(),
+        })
+    }).collect()
+}
+
+/// Extracts metadata from a log, falling back to the name of its album folder.
+///
+/// Folders named "Artist - Album (Year)" fill in whatever the log itself does not provide.
+pub fn read_metadata(path: &Path, album_folder: Option<&Path>) -> LogMetadata {
+    let mut metadata = read_log_text(path).map(|content| parse_metadata(&content)).unwrap_or_default();
+    if let Some(name) = album_folder.and_then(|folder| folder.file_name()).map(|name| name.to_string_lossy().into_owned()) {
+        let folder = FOLDER_REGEX.get_or_init(|| Regex::new(r"^(.+?)\s+-\s+(.+?)(?:\s*[(\[]((?:19|20)\d{2})[)\]])?$").unwrap());
+        if let Some(caps) = folder.captures(&name) {
+            metadata.artist.get_or_insert_with(|| caps[1].to_string());
+            metadata.album.get_or_insert_with(|| caps[2].to_string());
+            if let Some(year) = caps.get(3) {
+                metadata.year.get_or_insert_with(|| year.as_str().to_string());
+            }
+        }
+    }
+    metadata
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn foobar2000_track_lines_are_parsed() {
+        let log = "\
+foobar2000 1.6.16 / Dynamic Range Meter 1.1.1
+Analyzed: Artist / Album
+
+--------------------------------------------------------------------------------
+ DR         Peak         RMS     Duration Track
+--------------------------------------------------------------------------------
+ DR12      -0.10 dB   -14.42 dB      4:32 01-Opening Title
+ DRERR     -inf dB     -inf dB       0:02 02-Silence
+ DR9       +0.00 dB    -9.87 dB   1:02:03 03-Long Mix
+--------------------------------------------------------------------------------
+
+Number of tracks:  3
+Official DR value: DR11
+";
+        let tracks = parse_tracks(log);
+        assert_eq!(tracks.len(), 3);
+        assert_eq!((tracks[0].line, tracks[0].dr), (6, Some(12)));
+        assert_eq!((tracks[0].peak.as_str(), tracks[0].rms.as_str()), ("-0.10 dB", "-14.42 dB"));
+        assert_eq!((tracks[0].duration.as_str(), tracks[0].title.as_str()), ("4:32", "01-Opening Title"));
+        assert_eq!(tracks[1].dr, None);
+        assert_eq!((tracks[1].peak.as_str(), tracks[1].title.as_str()), ("-inf dB", "02-Silence"));
+        assert_eq!((tracks[2].dr, tracks[2].duration.as_str()), (Some(9), "1:02:03"));
+    }
+
+    #[test]
+    fn tt_dr_offline_meter_track_lines_are_parsed() {
+        let log = "\
+DR         Peak         RMS     Duration Filename
+DR14       -0,01 dB    -16,67 dB    05:48    01 - Track One.wav
+DRERR      -inf dB     -inf dB      00:00    02 - Empty.wav
+
+Official DR Value: DR14
+";
+        let tracks = parse_tracks(log);
+        assert_eq!(tracks.len(), 2);
+        assert_eq!((tracks[0].dr, tracks[0].peak.as_str(), tracks[0].rms.as_str()), (Some(14), "-0,01 dB", "-16,67 dB"));
+        assert_eq!((tracks[0].duration.as_str(), tracks[0].title.as_str()), ("05:48", "01 - Track One.wav"));
+        assert_eq!((tracks[1].line, tracks[1].dr, tracks[1].title.as_str()), (2, None, "02 - Empty.wav"));
+    }
+}
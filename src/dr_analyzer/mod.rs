@@ -1 +1,3 @@
-pub mod analyzer;
\ No newline at end of file
+pub mod analyzer;
+pub mod comparison;
+pub mod log_parser;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::collections::BTreeSet;
+use std::fs::{read_dir, remove_dir_all, symlink_metadata};
+use std::ops::Bound::{Excluded, Unbounded};
+use std::path::{Path, PathBuf};
+
+use crate::file_manager::file_ops::remove_path;
+use crate::file_manager::journal::{journaled, Operation};
+use crate::file_manager::safety::confine_to_roots;
+use crate::file_manager::templates::album_folder;
+
+/// A summary of everything that will be removed together with an album folder.
+#[derive(Clone, Debug, Default)]
+pub struct FolderTree {
+    /// One line per file or folder, indented by depth, for the dry-run preview.
+    pub lines: Vec<String>,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// Returns the innermost scan root that contains `path`.
+fn innermost_root<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    roots.iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+}
+
+/// Returns the album folders of the given logs, for `album_folder_target`.
+pub fn album_folders<'a>(paths: impl IntoIterator<Item = &'a Path>) -> BTreeSet<PathBuf> {
+    paths.into_iter().filter_map(album_folder).collect()
+}
+
+/// Resolves the album folder of a log that may be removed or moved as a whole.
+///
+/// The folder must lie strictly beneath one of the scan roots: a log directly inside a root
+/// (including a root nested in another one), or any path outside the roots, is refused so a
+/// removal or move can never reach above the root. A folder that contains one of the other
+/// `known_albums` is refused as well, since the log then sits in a folder that holds more than
+/// one album, such as a per-user download folder.
+pub fn album_folder_target(path: &Path, roots: &[PathBuf], known_albums: &BTreeSet<PathBuf>) -> Result<PathBuf, String> {
+    let folder = album_folder(path).ok_or_else(|| format!("{} has no album folder", path.display()))?;
+    if roots.contains(&folder) {
+        return Err(format!("{} is a scan root", folder.display()));
+    }
+    if innermost_root(&folder, roots).is_none() {
+        return Err(format!("{} is not inside a scan root", folder.display()));
+    }
+
+    // Paths sort by component, so the folders beneath `folder` directly follow it.
+    let contains_album = known_albums.range::<PathBuf, _>((Excluded(&folder), Unbounded))
+        .next()
+        .is_some_and(|next| next.starts_with(&folder));
+    if contains_album {
+        return Err(format!("{} contains other album folders", folder.display()));
+    }
+    Ok(folder)
+}
+
+/// Lists a folder recursively for the dry-run preview and adds up the size of its files.
+///
+/// Symbolic links are listed but not followed.
+pub fn folder_tree(folder: &Path) -> FolderTree {
+    let mut tree = FolderTree::default();
+    tree.lines.push(format!("{}/", folder.display()));
+    collect_tree(folder, 1, &mut tree);
+    tree
+}
+
+/// Appends the entries of `dir` to `tree`, sorted by name, descending into subfolders.
+fn collect_tree(dir: &Path, depth: usize, tree: &mut FolderTree) {
+    let Ok(entries) = read_dir(dir) else {
+        tree.lines.push(format!("{}(unreadable)", "    ".repeat(depth)));
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let Ok(metadata) = symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_dir() {
+            tree.lines.push(format!("{}{}/", "    ".repeat(depth), name));
+            collect_tree(&path, depth + 1, tree);
+        } else {
+            tree.file_count += 1;
+            tree.total_size += metadata.len();
+            tree.lines.push(format!("{}{} ({})", "    ".repeat(depth), name, glib::format_size(metadata.len())));
+        }
+    }
+}
+
+/// Removes an album folder with all of its contents.
+///
+/// Unless `permanently` is set, the folder is moved to the trash as a whole. The folder must
+/// resolve to a location inside one of the scan roots (see `confine_to_roots`). The removal
+/// is recorded in the journal.
+pub fn remove_album_folder(folder: &Path, roots: &[PathBuf], permanently: bool) -> Result<(), String> {
+    confine_to_roots(folder, roots)?;
+    if permanently {
+        journaled(Operation::Delete, folder, None, || remove_dir_all(folder)).map_err(|err| err.to_string())
+    } else {
+        remove_path(folder, false).map_err(|err| err.to_string())
+    }
+}
+
+/// Removes the folders above `folder` that became empty, stopping at the innermost scan root.
+///
+/// The root itself is never removed. Returns the folders that were removed.
+pub fn remove_empty_parents(folder: &Path, roots: &[PathBuf], permanently: bool) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    let Some(root) = innermost_root(folder, roots) else {
+        return removed;
+    };
+    let mut current = folder.parent();
+    while let Some(dir) = current {
+        if dir == root.as_path() || !dir.starts_with(root) {
+            break;
+        }
+        if !read_dir(dir).is_ok_and(|mut entries| entries.next().is_none())
+            || confine_to_roots(dir, roots).is_err()
+            || remove_path(dir, permanently).is_err() {
+            break;
+        }
+        removed.push(dir.to_path_buf());
+        current = dir.parent();
+    }
+    removed
+}
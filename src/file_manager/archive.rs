@@ -0,0 +1,208 @@
+use std::env::temp_dir;
+use std::fs::{create_dir_all, read, write, File};
+use std::io::{BufReader, Error, ErrorKind::NotFound, Read, Result};
+use std::path::{Component::Normal, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+
+/// The separator placed between an archive's path and the path of an entry inside it.
+///
+/// A log stored as `CD1/dr.txt` inside `/music/album.zip` is represented by the
+/// virtual path `/music/album.zip!/CD1/dr.txt`.
+pub const ARCHIVE_SEPARATOR: &str = "!/";
+
+/// The container formats that can be searched for logs without extracting them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Determines the archive format of a file from its name.
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if the file is a zip or tar(.gz) archive that can be searched for logs.
+pub fn is_archive(path: &Path) -> bool {
+    archive_kind(path).is_some()
+}
+
+/// Returns `true` if the entry name inside an archive looks like a DR log (`.txt` or `.log`).
+fn is_log_entry(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".txt") || name.ends_with(".log")
+}
+
+/// Builds the virtual path of an entry inside an archive, e.g. `album.zip!/CD1/dr.txt`.
+fn virtual_path(archive: &Path, entry: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}{}", archive.to_string_lossy(), ARCHIVE_SEPARATOR, entry.trim_start_matches('/')))
+}
+
+/// Splits a virtual path into the archive on disk and the name of the entry inside it.
+///
+/// Returns `None` for ordinary filesystem paths.
+pub fn split_virtual_path(path: &Path) -> Option<(PathBuf, String)> {
+    let text = path.to_string_lossy();
+    text.match_indices(ARCHIVE_SEPARATOR)
+        .map(|(index, _)| (PathBuf::from(&text[..index]), &text[index + ARCHIVE_SEPARATOR.len()..]))
+        .find(|(archive, _)| is_archive(archive))
+        .map(|(archive, entry)| (archive, entry.to_string()))
+}
+
+/// Returns `true` if the path points to an entry inside an archive rather than a real file.
+pub fn is_virtual_path(path: &Path) -> bool {
+    split_virtual_path(path).is_some()
+}
+
+/// Opens a tar archive, transparently decompressing it if it is gzipped.
+fn open_tar(path: &Path, kind: ArchiveKind) -> Result<Archive<Box<dyn Read>>> {
+    let file = BufReader::new(File::open(path)?);
+    let reader: Box<dyn Read> = if kind == ArchiveKind::TarGz {
+        Box::new(GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(Archive::new(reader))
+}
+
+/// Lists all `.txt` and `.log` entries of an archive as virtual paths.
+///
+/// Only the archive's index (zip) or headers (tar) are read; entry contents are skipped.
+pub fn list_archive_logs(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut logs = Vec::new();
+    match archive_kind(path) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+            for index in 0..archive.len() {
+                let entry = archive.by_index(index)?;
+                let name = entry.name()?;
+                if entry.is_file() && is_log_entry(&name) {
+                    logs.push(virtual_path(path, &name));
+                }
+            }
+        }
+        Some(kind) => {
+            let mut archive = open_tar(path, kind)?;
+            for entry in archive.entries()? {
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                if is_log_entry(&name) {
+                    logs.push(virtual_path(path, &name));
+                }
+            }
+        }
+        None => {}
+    }
+    Ok(logs)
+}
+
+/// Reads all `.txt` and `.log` entries of an archive in a single pass.
+///
+/// Returns the virtual path and the contents of every log. Unlike reading each entry with
+/// `read_archive_entry`, a tar(.gz) archive is only decompressed once.
+pub fn read_archive_logs(path: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut logs = Vec::new();
+    match archive_kind(path) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+            for index in 0..archive.len() {
+                let mut entry = archive.by_index(index)?;
+                let name = entry.name()?.to_string();
+                if entry.is_file() && is_log_entry(&name) {
+                    let mut bytes = Vec::new();
+                    entry.read_to_end(&mut bytes)?;
+                    logs.push((virtual_path(path, &name), bytes));
+                }
+            }
+        }
+        Some(kind) => {
+            let mut archive = open_tar(path, kind)?;
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                if is_log_entry(&name) {
+                    let mut bytes = Vec::new();
+                    entry.read_to_end(&mut bytes)?;
+                    logs.push((virtual_path(path, &name), bytes));
+                }
+            }
+        }
+        None => {}
+    }
+    Ok(logs)
+}
+
+/// Reads the contents of a single entry of an archive into memory.
+///
+/// The entry name is taken from a virtual path, which never starts with '/', so entries
+/// stored with a leading '/' are found as well.
+pub fn read_archive_entry(archive_path: &Path, entry_name: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    match archive_kind(archive_path) {
+        Some(ArchiveKind::Zip) => {
+            let mut archive = ZipArchive::new(BufReader::new(File::open(archive_path)?))?;
+            let index = archive.index_for_name(entry_name)
+                .or_else(|| archive.index_for_name(&format!("/{}", entry_name)))
+                .ok_or_else(|| Error::new(NotFound, format!("{} not found in {}", entry_name, archive_path.display())))?;
+            archive.by_index(index)?.read_to_end(&mut bytes)?;
+            Ok(bytes)
+        }
+        Some(kind) => {
+            let mut archive = open_tar(archive_path, kind)?;
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if entry.path()?.to_string_lossy().trim_start_matches('/') == entry_name {
+                    entry.read_to_end(&mut bytes)?;
+                    return Ok(bytes);
+                }
+            }
+            Err(Error::new(NotFound, format!("{} not found in {}", entry_name, archive_path.display())))
+        }
+        None => Err(Error::new(NotFound, format!("{} is not a supported archive", archive_path.display()))),
+    }
+}
+
+/// Reads a log from disk, or from inside an archive if `path` is a virtual path.
+pub fn read_log_bytes(path: &Path) -> Result<Vec<u8>> {
+    match split_virtual_path(path) {
+        Some((archive, entry)) => read_archive_entry(&archive, &entry),
+        None => read(path),
+    }
+}
+
+/// Extracts an archive entry to a temporary file so it can be opened by other applications.
+///
+/// The file is written to `$TMPDIR/drlogseeker/<archive name>/<entry path>`. Components of
+/// the entry path that could escape this directory (such as `..`) are dropped.
+pub fn extract_to_temp(path: &Path) -> Result<PathBuf> {
+    let (archive, entry) = split_virtual_path(path)
+        .ok_or_else(|| Error::new(NotFound, format!("{} is not inside an archive", path.display())))?;
+    let bytes = read_archive_entry(&archive, &entry)?;
+    let mut target = temp_dir().join("drlogseeker");
+    target.push(archive.file_name().unwrap_or_default());
+    target.extend(Path::new(&entry).components().filter(|c| matches!(c, Normal(_))));
+    if let Some(parent) = target.parent() {
+        create_dir_all(parent)?;
+    }
+    write(&target, bytes)?;
+    Ok(target)
+}
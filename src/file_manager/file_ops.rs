@@ -1,22 +1,40 @@
-use std::{io::Result, process::Command};
+use std::{error::Error, io::Result, process::Command};
+use std::collections::{HashSet, VecDeque};
 use std::fs::{read_dir, remove_dir, remove_file}; 
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::mpsc::Sender;
+use std::thread::scope;
+use std::time::Duration;
 
-use gio::ListStore;
+use gio::{Cancellable, File, ListStore};
+use gio::prelude::FileExt;
 use glib::clone;
-use gtk4::{ButtonsType::YesNo, DialogFlags, MessageDialog, MessageType::Warning, MultiSelection, ResponseType::Yes, StringObject};
-use libadwaita::ApplicationWindow;
-use libadwaita::prelude::{Cast, DialogExt, GtkWindowExt, ListModelExt, SelectionModelExt, WidgetExt};
+use gtk4::{MultiSelection, StringObject};
+use libadwaita::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use libadwaita::prelude::{Cast, GtkWindowExt, ListModelExt, MessageDialogExt, SelectionModelExt};
 
-use crate::data::app_state::AppState;
-use crate::dr_analyzer::analyzer::update_ui;
-use crate::ui::dialogs::show_error_dialog;
+use crate::data::app_state::{AppState, DRResult, ProblemHandler};
+use crate::data::settings::current_settings;
+use crate::dr_analyzer::analyzer::{update_rows, ScanControl};
+use crate::file_manager::archive::{extract_to_temp, is_archive, is_virtual_path, list_archive_logs, read_archive_logs, split_virtual_path};
+use crate::file_manager::journal::{journaled, Operation};
+use crate::file_manager::safety::{confine_to_roots, READ_ONLY_MESSAGE};
+use crate::file_manager::templates::physical_path;
+use crate::ui::dialogs::{report_problem_with_details, show_error_dialog};
 
 /// Attempts to open a file using the system's default application (`xdg-open`).
 ///
+/// Logs stored inside an archive are first extracted to a temporary file.
 /// If the file does not exist or the open command fails, it displays an error dialog.
 pub fn try_open_file(window: &ApplicationWindow, path: &Path) {
+    if is_virtual_path(path) {
+        match extract_to_temp(path) {
+            Ok(extracted) => try_open_file(window, &extracted),
+            Err(err) => show_error_dialog(window, &format!("Failed to extract {}: {}", path.display(), err)),
+        }
+        return;
+    }
     if !path.exists() {
         show_error_dialog(window, &format!("File not found: {}", path.display()));
         return;
@@ -28,102 +46,279 @@ pub fn try_open_file(window: &ApplicationWindow, path: &Path) {
 
 /// Recursively finds all files with `.txt` or `.log` extensions in a given directory.
 ///
+/// Zip and tar(.gz) archives are searched as well; their matching entries are added
+/// as virtual paths such as `album.zip!/CD1/dr.txt`. Archives that cannot be read are skipped.
 /// The discovered file paths are appended to the `files` vector.
-pub fn find_log_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+pub fn find_log_files(path: &Path, files: &mut Vec<PathBuf>, report_problem: &ProblemHandler) -> Result<()> {
     for entry in read_dir(path)? {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
-            find_log_files(&path, files)?;
-        } else if let Some(ext) = path.extension() {
-            if ext == "txt" || ext == "log" {
-                files.push(path);
+            find_log_files(&path, files, report_problem)?;
+        } else if is_archive(&path) {
+            match list_archive_logs(&path) {
+                Ok(entries) => files.extend(entries),
+                Err(err) => report_problem(format!("Failed to read archive {}: {}", path.display(), err)),
             }
+        } else if is_log_file(&path) {
+            files.push(path);
         }
     }
     Ok(())
 }
 
-/// Removes the selected files from the UI list and, if configured, from the filesystem.
+/// Returns `true` if the path has a `.txt` or `.log` extension.
+pub fn is_log_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "txt" || ext == "log")
+}
+
+/// Collects all log files that belong to a scan root.
 ///
-/// This function checks the `app_state` to determine if file deletion is enabled.
-/// If `delete_files` is true, it shows a confirmation dialog before permanently
-/// deleting the files. If `delete_folders` is also true, it will attempt to remove
-/// the parent directory of a deleted file if it becomes empty.
+/// A root can be a directory (searched recursively), an archive (its log entries are listed)
+/// or a single `.txt`/`.log` file, which is returned as is. Other files yield no logs.
+pub fn collect_root_files(root: &Path, report_problem: &ProblemHandler) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if root.is_dir() {
+        find_log_files(root, &mut files, report_problem)?;
+    } else if is_archive(root) {
+        files = list_archive_logs(root)?;
+    } else if is_log_file(root) {
+        files.push(root.to_path_buf());
+    }
+    Ok(files)
+}
+
+/// Sends the logs inside an archive to `sender`, together with their contents.
 ///
-/// If `delete_files` is false, it only removes the items from the application's internal list.
-pub fn delete_selected_files(window: &ApplicationWindow, selection_model: &MultiSelection, 
-                        list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
-    let selected_items: Vec<_> = (0..selection_model.n_items())
-        .filter(|&i| selection_model.is_selected(i))
-        .collect();
-    if selected_items.is_empty() {
-        return;
+/// The archive is read in a single pass, so that the analyzers do not have to open and
+/// decompress it again for every entry. Archives that cannot be read are reported and skipped.
+fn send_archive_logs(path: &Path, sender: &Sender<(PathBuf, Option<Vec<u8>>)>, report_problem: &ProblemHandler) {
+    match read_archive_logs(path) {
+        Ok(logs) => logs.into_iter().for_each(|(log, bytes)| { sender.send((log, Some(bytes))).ok(); }),
+        Err(err) => report_problem(format!("Failed to read archive {}: {}", path.display(), err)),
     }
-    let mut paths_to_remove = Vec::new();
-    if let Ok(_) = app_state.lock() {
-        for &index in &selected_items {
-            if let Some(item) = selection_model.item(index) {
-                if let Some(string_obj) = item.downcast_ref::<StringObject>() {
-                    let path = PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""));
-                    paths_to_remove.push(path);
+}
+
+/// Walks the scan roots with a pool of `num_threads` threads and sends every log file to `sender`.
+///
+/// Directories are taken from a shared queue, so large trees are traversed in parallel and
+/// files are sent as soon as they are found rather than after the whole walk. Roots that are
+/// archives or single log files are handled like in `collect_root_files`. Logs inside
+/// archives are sent with their contents (see `send_archive_logs`), other files without.
+/// Unreadable directories and archives are skipped. The walk pauses and stops with the
+/// scan's `control`. The function returns once the walk is complete or cancelled.
+pub fn walk_roots_parallel(roots: &[PathBuf], num_threads: usize, control: &ScanControl, sender: Sender<(PathBuf, Option<Vec<u8>>)>,
+                           report_problem: &ProblemHandler) {
+
+    // The queue holds the directories still to be read and the number of directories being read.
+    let queue = (Mutex::new((VecDeque::new(), 0usize)), Condvar::new());
+    for root in roots {
+        if root.is_dir() {
+            queue.0.lock().unwrap().0.push_back(root.clone());
+        } else if is_archive(root) {
+            send_archive_logs(root, &sender, report_problem);
+        } else if is_log_file(root) {
+            sender.send((root.clone(), None)).ok();
+        }
+    }
+    scope(|scope| {
+        for _ in 0..num_threads.max(1) {
+            let (queue, sender) = (&queue, sender.clone());
+            scope.spawn(move || {
+                let (lock, condvar) = queue;
+                loop {
+
+                    // Take the next directory, or stop once the queue is empty and no thread can add more.
+                    let dir = {
+                        let mut guard = lock.lock().unwrap();
+                        loop {
+                            if control.is_cancelled() {
+                                break None;
+                            }
+                            if let Some(dir) = guard.0.pop_front() {
+                                guard.1 += 1;
+                                break Some(dir);
+                            }
+                            if guard.1 == 0 {
+                                break None;
+                            }
+                            guard = condvar.wait_timeout(guard, Duration::from_millis(100)).unwrap().0;
+                        }
+                    };
+                    let Some(dir) = dir else {
+                        condvar.notify_all();
+                        break;
+                    };
+                    if !control.wait_while_paused() {
+                        lock.lock().unwrap().1 -= 1;
+                        condvar.notify_all();
+                        break;
+                    }
+                    match read_dir(&dir) {
+                        Ok(entries) => {
+                            for path in entries.flatten().map(|entry| entry.path()) {
+                                if path.is_dir() {
+                                    lock.lock().unwrap().0.push_back(path);
+                                    condvar.notify_one();
+                                } else if is_archive(&path) {
+                                    send_archive_logs(&path, &sender, report_problem);
+                                } else if is_log_file(&path) {
+                                    sender.send((path, None)).ok();
+                                }
+                            }
+                        }
+                        Err(err) => report_problem(format!("Failed to read directory {}: {}", dir.display(), err)),
+                    }
+                    let mut guard = lock.lock().unwrap();
+                    guard.1 -= 1;
+                    if guard.0.is_empty() && guard.1 == 0 {
+                        condvar.notify_all();
+                    }
                 }
-            }
+            });
+        }
+    });
+}
+
+/// Returns `true` if `path` is the scan root itself or lies beneath it.
+///
+/// Entries inside an archive root (e.g. `album.zip!/dr.txt`) are considered part of that root.
+pub fn root_contains(root: &Path, path: &Path) -> bool {
+    path.starts_with(root) || split_virtual_path(path).is_some_and(|(archive, _)| archive.starts_with(root))
+}
+
+/// Moves a file or an empty folder to the trash, or deletes it permanently.
+///
+/// Trashing uses `gio::File::trash`, which follows the freedesktop.org trash specification.
+/// It fails on filesystems without a trash directory (e.g. some network or removable mounts).
+/// Every attempt is recorded in the journal.
+pub fn remove_path(path: &Path, permanently: bool) -> std::result::Result<(), Box<dyn Error>> {
+    let operation = if permanently { Operation::Delete } else { Operation::Trash };
+    journaled(operation, path, None, || -> std::result::Result<(), Box<dyn Error>> {
+        if permanently {
+            if path.is_dir() { remove_dir(path)? } else { remove_file(path)? }
+        } else {
+            File::for_path(path).trash(Cancellable::NONE)?;
         }
+        Ok(())
+    })
+}
+
+/// Returns the paths of all rows that are currently selected in the results view.
+pub fn selected_paths(selection_model: &MultiSelection) -> Vec<PathBuf> {
+    (0..selection_model.n_items())
+        .filter(|&i| selection_model.is_selected(i))
+        .filter_map(|i| selection_model.item(i))
+        .filter_map(|item| item.downcast_ref::<StringObject>().map(|string_obj| {
+            PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""))
+        }))
+        .collect()
+}
+
+/// Removes the selected files from the UI list and, if configured, from the filesystem.
+///
+/// See `remove_files` for how the `AppState` settings control what happens on disk.
+pub fn delete_selected_files(window: &ApplicationWindow, selection_model: &MultiSelection, 
+                        list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let paths_to_remove = selected_paths(selection_model);
+    if paths_to_remove.is_empty() {
+        return;
     }
-    let should_confirm = if let Ok(state) = app_state.lock() {
-        state.delete_files
-    } else {
-        false
-    };
+    remove_files(window, paths_to_remove, list_store, app_state);
+}
+
+/// Removes files from the UI list and, if configured, from the filesystem.
+///
+/// This function checks the `app_state` to determine if file deletion is enabled.
+/// If `delete_files` is true, it shows a confirmation dialog before deleting the files.
+/// By default, files are moved to the trash; only if `delete_permanently` is set are they
+/// removed for good. If `delete_folders` is also true, it will attempt to remove the parent
+/// directory of a deleted file if it becomes empty.
+///
+/// Files that cannot be deleted (for example on mounts without a trash) stay in the list
+/// and are reported in a toast. Entries inside archives are only removed from the list.
+///
+/// If `delete_files` is false or read-only mode is enabled, it only removes the items from
+/// the application's internal list.
+pub fn remove_files(window: &ApplicationWindow, paths_to_remove: Vec<PathBuf>,
+                    list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let settings = current_settings();
+    let (should_confirm, permanently, delete_folders) =
+        (settings.delete_files && !settings.read_only, settings.delete_permanently, settings.delete_folders);
     if should_confirm {
         let dialog = MessageDialog::new(
             Some(window),
-            DialogFlags::MODAL,
-            Warning,
-            YesNo,
-            &format!("This will permanently delete {} file(s) from your system{} Continue?", 
+            Some(if permanently { "Delete Files Permanently?" } else { "Move Files to Trash?" }),
+            Some(&format!("This will {} {} file(s){}{}",
+                if permanently { "permanently delete" } else { "move" },
                 paths_to_remove.len(),
-                if let Ok(state) = app_state.lock() {
-                    if state.delete_folders { " and their parent folders" } else { "" }
-                } else { "" }
-            )
+                if delete_folders { " and their empty parent folders" } else { "" },
+                if permanently { " from your system." } else { " to the trash." }
+            ))
         );
-        if let Some(button) = dialog.widget_for_response(Yes) {
-            button.add_css_class("destructive-action");
-        }
-        let paths_to_remove_clone = paths_to_remove.clone();
-        dialog.connect_response(clone!(@strong app_state, @strong list_store => move |dialog, response| {
-            if response == Yes {
-                if let Ok(mut state) = app_state.lock() {
-                    // Delete files from system
-                    for path in &paths_to_remove_clone {
-                        if let Err(err) = remove_file(path) {
-                            eprintln!("Failed to delete file {}: {}", path.display(), err);
-                        } else if state.delete_folders {
-                            // Try to remove parent folder if it's empty
-                            if let Some(parent) = path.parent() {
-                                if let Ok(entries) = read_dir(parent) {
-                                    if entries.count() == 0 {
-                                        if let Err(err) = remove_dir(parent) {
-                                            eprintln!("Failed to delete empty folder {}: {}", parent.display(), err);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    state.results.retain(|result| !paths_to_remove_clone.contains(&result.path));
-                    update_ui(&list_store, &state.results);
-                }
+        dialog.add_response("cancel", "Cancel");
+        dialog.add_response("delete", if permanently { "Delete" } else { "Move to Trash" });
+        dialog.set_response_appearance("delete", ResponseAppearance::Destructive);
+        dialog.set_default_response(Some("cancel"));
+        dialog.set_close_response("cancel");
+        dialog.connect_response(None, clone!(@weak window, @strong app_state, @strong list_store => move |_, response| {
+            if response == "delete" {
+                delete_paths(&window, &paths_to_remove, permanently, delete_folders, &list_store, &app_state);
             }
-            dialog.close();
         }));
-        dialog.show();
-    } else {
-        if let Ok(mut state) = app_state.lock() {
-            state.results.retain(|result| !paths_to_remove.contains(&result.path));
-            update_ui(&list_store, &state.results);
+        dialog.present();
+    } else if let Ok(mut state) = app_state.lock() {
+        let removed: Vec<DRResult> = state.results.extract_if(.., |result| paths_to_remove.contains(&result.path)).collect();
+        update_rows(list_store, &removed, &[]);
+    }
+}
+
+/// Deletes files from the filesystem and removes them from the UI list, without asking.
+///
+/// This is the part of `remove_files` that runs after the user confirmed the deletion.
+/// Files are moved to the trash unless `permanently` is set. If `delete_folders` is true,
+/// parent folders that become empty are removed as well. Files that cannot be deleted stay
+/// in the list and are reported in a toast; entries inside archives are only
+/// removed from the list. When an archive itself is deleted, the entries inside it are
+/// removed from the list as well. Nothing is deleted in read-only mode, or outside the scan
+/// roots (see `confine_to_roots`).
+pub fn delete_paths(window: &ApplicationWindow, paths_to_remove: &[PathBuf], permanently: bool, delete_folders: bool,
+                    list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let mut failures = Vec::new();
+    let read_only = current_settings().read_only;
+    if let Ok(mut state) = app_state.lock() {
+        let mut removed = HashSet::new();
+        for path in paths_to_remove {
+            if is_virtual_path(path) {
+                removed.insert(path.clone()); // Entries inside archives are only removed from the list.
+            } else if read_only {
+                failures.push(format!("{}: {}", path.display(), READ_ONLY_MESSAGE));
+            } else if let Err(err) = confine_to_roots(path, &state.roots) {
+                failures.push(format!("{}: {}", path.display(), err));
+            } else if let Err(err) = remove_path(path, permanently) {
+                failures.push(format!("{}: {}", path.display(), err));
+            } else {
+                removed.insert(path.clone());
+
+                // Try to remove parent folder if it's empty
+                if delete_folders
+                    && let Some(parent) = path.parent()
+                    && read_dir(parent).is_ok_and(|entries| entries.count() == 0)
+                    && confine_to_roots(parent, &state.roots).is_ok()
+                    && let Err(err) = remove_path(parent, permanently) {
+                    failures.push(format!("{}: {}", parent.display(), err));
+                }
+            }
         }
+        let removed_results: Vec<DRResult> = state.results
+            .extract_if(.., |result| removed.contains(&result.path) || removed.contains(&physical_path(&result.path)))
+            .collect();
+        update_rows(list_store, &removed_results, &[]);
     }
-}
\ No newline at end of file
+    if !failures.is_empty() {
+        report_problem_with_details(window, &format!("Could not {} {} item(s)",
+            if permanently { "delete" } else { "move to the trash" },
+            failures.len()
+        ), failures.join("\n"));
+    }
+}
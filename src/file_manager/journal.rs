@@ -0,0 +1,211 @@
+use std::fs::{create_dir_all, read_dir, read_to_string, symlink_metadata, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use gio::{Cancellable, File, FileCopyFlags, FileQueryInfoFlags};
+use gio::prelude::FileExt;
+use glib::{user_state_dir, DateTime};
+
+/// The name of the journal file inside the application's state directory.
+const JOURNAL_FILE_NAME: &str = "journal.tsv";
+
+/// Receives the messages of journal writes that failed; see `set_journal_error_handler`.
+static ERROR_HANDLER: OnceLock<fn(String)> = OnceLock::new();
+
+/// A destructive file operation recorded in the journal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Trash,
+    Delete,
+    Move,
+    Rename,
+    /// A file restored from the trash through the History window.
+    Restore,
+}
+
+impl Operation {
+
+    /// Returns the name used for the operation in the journal file.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Operation::Trash => "trash",
+            Operation::Delete => "delete",
+            Operation::Move => "move",
+            Operation::Rename => "rename",
+            Operation::Restore => "restore",
+        }
+    }
+
+    /// Parses an operation name written by `as_str`.
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "trash" => Some(Operation::Trash),
+            "delete" => Some(Operation::Delete),
+            "move" => Some(Operation::Move),
+            "rename" => Some(Operation::Rename),
+            "restore" => Some(Operation::Restore),
+            _ => None,
+        }
+    }
+}
+
+/// A single line of the journal.
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    /// The local time of the operation in ISO 8601 format.
+    pub timestamp: String,
+    pub operation: Operation,
+    pub path: PathBuf,
+    /// The new location for moves, renames and restores.
+    pub destination: Option<PathBuf>,
+    /// The size of the file, or of all files in the folder, before the operation.
+    pub size: u64,
+    /// `None` if the operation succeeded, otherwise the error message.
+    pub error: Option<String>,
+}
+
+/// Returns the path of the journal file in the XDG state directory.
+pub fn journal_path() -> PathBuf {
+    user_state_dir().join("drlogseeker").join(JOURNAL_FILE_NAME)
+}
+
+/// Returns the size of a file, or the total size of all files beneath a folder.
+///
+/// Symbolic links are not followed.
+fn path_size(path: &Path) -> u64 {
+    match symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => read_dir(path)
+            .map(|entries| entries.flatten().map(|entry| path_size(&entry.path())).sum())
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Escapes the characters that separate fields and lines in the journal.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+/// Reverts `escape`.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Sets the function that reports failures to write the journal, such as a toast.
+///
+/// Only the first handler is kept. Without a handler, failures are printed.
+pub fn set_journal_error_handler(handler: fn(String)) {
+    let _ = ERROR_HANDLER.set(handler);
+}
+
+/// Appends an entry to the journal.
+///
+/// Failures to write the journal are passed to the error handler, but never stop the
+/// operation itself.
+pub fn append_entry(entry: &JournalEntry) {
+    let path = journal_path();
+    let line = [
+        escape(&entry.timestamp),
+        entry.operation.as_str().to_string(),
+        escape(&entry.path.to_string_lossy()),
+        escape(&entry.destination.as_ref().map(|d| d.to_string_lossy().into_owned()).unwrap_or_default()),
+        entry.size.to_string(),
+        entry.error.as_deref().map(escape).unwrap_or_else(|| "ok".to_string()),
+    ].join("\t");
+    let written = path.parent().map_or(Ok(()), create_dir_all)
+        .and_then(|_| OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| writeln!(file, "{}", line));
+    if let Err(err) = written {
+        let message = format!("Failed to write journal {}: {}", path.display(), err);
+        match ERROR_HANDLER.get() {
+            Some(handler) => handler(message),
+            None => eprintln!("{}", message),
+        }
+    }
+}
+
+/// Performs a destructive file operation and records it in the journal.
+///
+/// The size of `path` is measured before `perform` runs. Returns the result of `perform`.
+pub fn journaled<E: ToString>(operation: Operation, path: &Path, destination: Option<&Path>,
+                              perform: impl FnOnce() -> Result<(), E>) -> Result<(), E> {
+    let size = path_size(path);
+    let outcome = perform();
+    append_entry(&JournalEntry {
+        timestamp: DateTime::now_local().and_then(|now| now.format_iso8601()).map(|s| s.to_string()).unwrap_or_default(),
+        operation,
+        path: path.to_path_buf(),
+        destination: destination.map(Path::to_path_buf),
+        size,
+        error: outcome.as_ref().err().map(ToString::to_string),
+    });
+    outcome
+}
+
+/// Reads all entries of the journal, oldest first.
+///
+/// Lines that cannot be parsed are skipped. A missing journal yields no entries.
+pub fn read_journal() -> Vec<JournalEntry> {
+    let Ok(content) = read_to_string(journal_path()) else {
+        return Vec::new();
+    };
+    content.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [timestamp, operation, path, destination, size, outcome] = fields[..] else {
+            return None;
+        };
+        Some(JournalEntry {
+            timestamp: unescape(timestamp),
+            operation: Operation::parse(operation)?,
+            path: PathBuf::from(unescape(path)),
+            destination: Some(unescape(destination)).filter(|d| !d.is_empty()).map(PathBuf::from),
+            size: size.parse().unwrap_or(0),
+            error: (outcome != "ok").then(|| unescape(outcome)),
+        })
+    }).collect()
+}
+
+/// Restores a file or folder from the trash to its original location.
+///
+/// The trash is searched for an item whose original path is `path`; if there are several,
+/// the one trashed most recently is restored. Missing parent folders are recreated, and an
+/// existing file at `path` is never overwritten. The restore is recorded in the journal.
+pub fn restore_from_trash(path: &Path) -> Result<(), String> {
+    let trash = File::for_uri("trash:///");
+    let children = trash
+        .enumerate_children("standard::name,trash::orig-path,trash::deletion-date", FileQueryInfoFlags::NONE, Cancellable::NONE)
+        .map_err(|err| err.to_string())?;
+    let mut candidates: Vec<(String, PathBuf)> = children
+        .flatten()
+        .filter(|info| info.attribute_byte_string("trash::orig-path").is_some_and(|orig| Path::new(orig.as_str()) == path))
+        .map(|info| (info.attribute_as_string("trash::deletion-date").map(|d| d.to_string()).unwrap_or_default(), info.name()))
+        .collect();
+    candidates.sort();
+    let Some((_, name)) = candidates.pop() else {
+        return Err("the item is no longer in the trash".to_string());
+    };
+    let trashed = trash.child(name);
+    journaled(Operation::Restore, path, None, || {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        trashed.move_(&File::for_path(path), FileCopyFlags::NOFOLLOW_SYMLINKS, Cancellable::NONE, None)
+            .map_err(|err| err.to_string())
+    })
+}
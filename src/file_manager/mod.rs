@@ -1 +1,9 @@
-pub mod file_ops;
\ No newline at end of file
+pub mod album_cleanup;
+pub mod archive;
+pub mod file_ops;
+pub mod journal;
+pub mod organizer;
+pub mod renamer;
+pub mod safety;
+pub mod templates;
+pub mod watcher;
\ No newline at end of file
@@ -0,0 +1,197 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs::{copy, create_dir_all, read_dir, remove_dir_all, remove_file, rename, symlink_metadata};
+use std::io::{Error, ErrorKind::{AlreadyExists, CrossesDevices}, Result};
+use std::path::{Path, PathBuf};
+
+use crate::data::app_state::DRResult;
+use crate::file_manager::album_cleanup::album_folder_target;
+use crate::file_manager::journal::{journaled, Operation};
+use crate::file_manager::templates::{expand_template, physical_path, TemplateValues};
+
+/// What is moved for each selected log.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveTarget {
+    /// Only the log file (or the archive containing it).
+    LogFile,
+    /// The whole album folder containing the log.
+    AlbumFolder,
+}
+
+/// How to handle a destination that already exists.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictPolicy {
+    /// Leave the source where it is.
+    Skip,
+    /// Append " (2)", " (3)", … to the destination name until it is free.
+    Rename,
+}
+
+/// A single move computed by `plan_moves`, shown in the preview before anything is changed.
+#[derive(Clone, Debug)]
+pub struct PlannedMove {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// `true` if the destination had to be renamed to avoid a conflict.
+    pub renamed: bool,
+    /// `true` if the move will not be performed because of a conflict.
+    pub skipped: bool,
+    /// Why the album folder of the log cannot be moved, if it cannot (see `album_folder_target`).
+    pub refused: Option<String>,
+}
+
+impl PlannedMove {
+
+    /// Returns `true` if the move is performed, i.e. it is neither skipped nor refused.
+    pub fn is_performed(&self) -> bool {
+        !self.skipped && self.refused.is_none()
+    }
+}
+
+/// Computes where each selected log (or its album folder) will be moved.
+///
+/// The destination of every source is `destination_root/<expanded template>`; when only
+/// log files are moved, the file name is appended to it. Several logs in the same album
+/// folder produce a single folder move. Album folders are resolved like for removals
+/// (see `album_folder_target`), so a folder holding other albums, such as a per-user
+/// download folder, is refused. Destinations that already exist, or that are claimed by an
+/// earlier entry of the plan, are resolved with `policy`.
+pub fn plan_moves(items: &[(DRResult, TemplateValues)], destination_root: &Path, template: &str, target: MoveTarget,
+                  policy: ConflictPolicy, roots: &[PathBuf], known_albums: &BTreeSet<PathBuf>) -> Vec<PlannedMove> {
+    let mut plan = Vec::new();
+    let mut sources = HashSet::new();
+    let mut claimed = HashSet::new();
+    for (result, values) in items {
+        let source = match target {
+            MoveTarget::LogFile => physical_path(&result.path),
+            MoveTarget::AlbumFolder => match album_folder_target(&result.path, roots, known_albums) {
+                Ok(folder) => folder,
+                Err(reason) => {
+                    let source = result.path.clone();
+                    plan.push(PlannedMove { source, destination: PathBuf::new(), renamed: false, skipped: true, refused: Some(reason) });
+                    continue;
+                }
+            },
+        };
+        if !sources.insert(source.clone()) {
+            continue; // Another log of the same album (or archive) already moves this source.
+        }
+        let mut destination = destination_root.join(expand_template(template, values));
+        if target == MoveTarget::LogFile {
+            destination.push(source.file_name().unwrap_or_default());
+        }
+        let mut planned = PlannedMove { source, destination, renamed: false, skipped: false, refused: None };
+        if planned.destination.exists() || claimed.contains(&planned.destination) {
+            match policy {
+                ConflictPolicy::Skip => planned.skipped = true,
+                ConflictPolicy::Rename => {
+                    planned.destination = free_destination(&planned.destination, &claimed);
+                    planned.renamed = true;
+                }
+            }
+        }
+        if !planned.skipped {
+            claimed.insert(planned.destination.clone());
+        }
+        plan.push(planned);
+    }
+    plan
+}
+
+/// Finds the first name of the form "name (n).ext" that neither exists nor is claimed.
+fn free_destination(destination: &Path, claimed: &HashSet<PathBuf>) -> PathBuf {
+    let stem = destination.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let extension = destination.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    (2..)
+        .map(|n| destination.with_file_name(format!("{} ({}){}", stem, n, extension)))
+        .find(|candidate| !candidate.exists() && !claimed.contains(candidate))
+        .unwrap()
+}
+
+/// Moves a file or folder, falling back to copy-and-delete across filesystems.
+///
+/// Missing parent folders of the destination are created. An existing destination is never
+/// overwritten, and a copy that fails partway is removed again. Every attempt is recorded in
+/// the journal.
+pub fn move_path(source: &Path, destination: &Path) -> Result<()> {
+    journaled(Operation::Move, source, Some(destination), || {
+        if destination.exists() {
+            return Err(Error::new(AlreadyExists, format!("{} already exists", destination.display())));
+        }
+        if let Some(parent) = destination.parent() {
+            create_dir_all(parent)?;
+        }
+        match rename(source, destination) {
+            Err(err) if err.kind() == CrossesDevices => {
+
+                // Remove a partial copy, so that the move can be retried.
+                if let Err(err) = copy_recursive(source, destination) {
+                    let _ = if destination.is_dir() { remove_dir_all(destination) } else { remove_file(destination) };
+                    return Err(err);
+                }
+                if source.is_dir() { remove_dir_all(source) } else { remove_file(source) }
+            }
+            other => other,
+        }
+    })
+}
+
+/// Copies a file, or a folder with all of its contents.
+fn copy_recursive(source: &Path, destination: &Path) -> Result<()> {
+    if symlink_metadata(source)?.is_dir() {
+        create_dir_all(destination)?;
+        for entry in read_dir(source)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        copy(source, destination)?;
+    }
+    Ok(())
+}
+
+/// Returns the new location of `path` after `from` has been moved to `to`.
+///
+/// Returns `None` if `path` is not `from` itself or located beneath it. Virtual paths of
+/// entries inside a moved archive are rebased as well.
+pub fn rebase_path(path: &Path, from: &Path, to: &Path) -> Option<PathBuf> {
+    if let Ok(relative) = path.strip_prefix(from) {
+        return Some(if relative.as_os_str().is_empty() { to.to_path_buf() } else { to.join(relative) });
+    }
+    let (path, from) = (path.to_string_lossy(), from.to_string_lossy());
+    path.strip_prefix(from.as_ref())
+        .filter(|rest| rest.starts_with('!'))
+        .map(|rest| PathBuf::from(format!("{}{}", to.to_string_lossy(), rest)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_manager::album_cleanup::album_folders;
+
+    use super::*;
+
+    /// Creates a scanned result for the log at `path`.
+    fn item(path: &str) -> (DRResult, TemplateValues) {
+        let path = PathBuf::from(path);
+        let result = DRResult {
+            dr_value: Some(10),
+            filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path,
+            scanned: true,
+            is_new: false,
+            marked: false,
+            invalid: false,
+        };
+        (result, TemplateValues { name: "Album".to_string(), ..TemplateValues::default() })
+    }
+
+    #[test]
+    fn folders_holding_other_albums_are_not_moved() {
+        let roots = [PathBuf::from("/music")];
+        let items = [item("/music/user/dr.txt"), item("/music/user/Album/dr.txt")];
+        let known = album_folders(items.iter().map(|(result, _)| result.path.as_path()));
+        let plan = plan_moves(&items, Path::new("/sorted"), "{name}", MoveTarget::AlbumFolder, ConflictPolicy::Skip, &roots, &known);
+        assert!(plan[0].refused.is_some() && !plan[0].is_performed());
+        assert_eq!(plan[1].source, PathBuf::from("/music/user/Album"));
+        assert!(plan[1].is_performed());
+    }
+}
@@ -0,0 +1,112 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::data::app_state::DRResult;
+use crate::file_manager::templates::{album_folder, expand_template, TemplateValues};
+
+/// A single album folder rename computed by `plan_renames`, shown in the preview first.
+#[derive(Clone, Debug)]
+pub struct PlannedRename {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// Why the rename is refused, if it is.
+    pub refused: Option<String>,
+}
+
+/// Computes the new name of the album folder of each selected log.
+///
+/// The template is expanded into a single folder name that replaces the current one in
+/// the same parent folder. Several logs in the same album folder produce a single rename,
+/// using the values of the first of them. A rename is refused if the template expands to
+/// nothing or to a nested path, if the folder is a scan root, if the name does not change,
+/// if the new name already exists, or if several folders would be renamed to the same name.
+///
+/// The renames are ordered deepest folder first, so that renaming an album folder never
+/// moves a nested folder that is renamed later in the same batch.
+pub fn plan_renames(items: &[(DRResult, TemplateValues)], roots: &[PathBuf], template: &str) -> Vec<PlannedRename> {
+    let mut plan: Vec<PlannedRename> = Vec::new();
+    let mut sources = HashSet::new();
+    for (result, values) in items {
+        let Some(source) = album_folder(&result.path) else {
+            continue;
+        };
+        if !sources.insert(source.clone()) {
+            continue; // Another log of the same album already renames this folder.
+        }
+        let name = expand_template(template, values);
+        let destination = source.with_file_name(&name);
+        let refused = if name.as_os_str().is_empty() {
+            Some("the template expands to an empty name".to_string())
+        } else if name.components().count() > 1 {
+            Some("the new name must not contain a slash".to_string())
+        } else if roots.contains(&source) {
+            Some("scan roots cannot be renamed".to_string())
+        } else if destination == source {
+            Some("the name is unchanged".to_string())
+        } else if destination.exists() {
+            Some("a folder with this name already exists".to_string())
+        } else {
+            None
+        };
+        plan.push(PlannedRename { source, destination, refused });
+    }
+
+    // Refuse every rename whose new name is claimed more than once.
+    let mut claims: HashMap<PathBuf, usize> = HashMap::new();
+    for planned in plan.iter().filter(|planned| planned.refused.is_none()) {
+        *claims.entry(planned.destination.clone()).or_default() += 1;
+    }
+    for planned in plan.iter_mut().filter(|planned| planned.refused.is_none()) {
+        if claims[&planned.destination] > 1 {
+            planned.refused = Some("another selected folder would get the same name".to_string());
+        }
+    }
+    plan.sort_by_key(|planned| Reverse(planned.source.components().count()));
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all, rename, write};
+    use std::path::Path;
+
+    use super::*;
+
+    /// Creates a scanned result and the template values that give its album folder `name`.
+    fn item(path: &Path, name: &str) -> (DRResult, TemplateValues) {
+        let result = DRResult {
+            dr_value: Some(10),
+            filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path: path.to_path_buf(),
+            scanned: true,
+            is_new: false,
+            marked: false,
+            invalid: false,
+        };
+        (result, TemplateValues { name: name.to_string(), ..TemplateValues::default() })
+    }
+
+    #[test]
+    fn nested_folders_are_renamed_deepest_first() {
+        let root = temp_dir().join(format!("drlogseeker-renamer-{}", std::process::id()));
+        let parent = root.join("Parent");
+        let child = parent.join("Child");
+        create_dir_all(&child).unwrap();
+        write(parent.join("dr.txt"), "").unwrap();
+        write(child.join("dr.txt"), "").unwrap();
+
+        // The parent is selected first, so it would be renamed first without the ordering.
+        let items = [item(&parent.join("dr.txt"), "Parent 2"), item(&child.join("dr.txt"), "Child 2")];
+        let plan = plan_renames(&items, std::slice::from_ref(&root), "{name}");
+        assert_eq!(plan.iter().map(|planned| planned.source.clone()).collect::<Vec<_>>(), [child.clone(), parent.clone()]);
+        assert!(plan.iter().all(|planned| planned.refused.is_none()));
+
+        for planned in &plan {
+            rename(&planned.source, &planned.destination).unwrap();
+        }
+        assert!(root.join("Parent 2").join("Child 2").join("dr.txt").exists());
+        remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use crate::file_manager::templates::physical_path;
+
+/// The message shown when an action is refused because read-only mode is enabled.
+pub const READ_ONLY_MESSAGE: &str = "Read-only mode is enabled. Disable it in the header bar to change files.";
+
+/// Resolves a path to its canonical form without following a symbolic link at the end.
+///
+/// Only the parent folder is canonicalized, so removing or moving a link affects the link
+/// itself, while links to folders outside a root along the way are resolved.
+fn canonical_location(path: &Path) -> Result<PathBuf, String> {
+    let path = physical_path(path);
+    let name = path.file_name().ok_or_else(|| "the path has no file name".to_string())?;
+    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let parent = parent.canonicalize().map_err(|err| err.to_string())?;
+    Ok(parent.join(name))
+}
+
+/// Checks that a destructive file operation on `path` stays inside an active scan root.
+///
+/// Both the path and the roots are resolved to canonical paths first, so paths reaching
+/// outside through symbolic links or `..` components are refused. Entries inside archives
+/// are checked by their archive. A scan root folder itself is refused as well, even if it is
+/// nested in another root; a single log file added as a root may be changed. Returns the
+/// canonical path on success, or the reason the path was refused.
+pub fn confine_to_roots(path: &Path, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let canonical = canonical_location(path)?;
+    let roots: Vec<PathBuf> = roots.iter().filter_map(|root| root.canonicalize().ok()).collect();
+
+    // Check every root for equality first, so a root nested in another one is refused as well.
+    if roots.iter().any(|root| canonical == *root && root.is_dir()) {
+        return Err("scan roots cannot be changed".to_string());
+    }
+    if roots.iter().any(|root| canonical.starts_with(root)) {
+        return Ok(canonical);
+    }
+    Err("the path is outside of the scan roots".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs::{create_dir_all, remove_dir_all};
+
+    use super::*;
+
+    #[test]
+    fn nested_roots_are_refused_in_any_order() {
+        let outer = temp_dir().join(format!("drlogseeker-safety-{}", std::process::id()));
+        let inner = outer.join("inner");
+        create_dir_all(inner.join("album")).unwrap();
+
+        let roots = [outer.clone(), inner.clone()];
+        assert!(confine_to_roots(&inner, &roots).is_err());
+        assert!(confine_to_roots(&outer, &roots).is_err());
+        assert!(confine_to_roots(&inner.join("album"), &roots).is_ok());
+        remove_dir_all(&outer).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use std::path::{Component::Normal, Path, PathBuf};
+
+use crate::data::app_state::DRResult;
+use crate::data::tiers::tier_for;
+use crate::dr_analyzer::log_parser::read_metadata;
+use crate::file_manager::archive::split_virtual_path;
+use crate::file_manager::file_ops::root_contains;
+
+/// The tokens that can be used in move and rename templates.
+pub const TEMPLATE_TOKENS: &str = "{dr} {tier} {artist} {album} {year} {user} {name}";
+
+/// The values substituted for the tokens of a template, gathered for one log.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateValues {
+    /// The DR value, or "ERR" if the log has none.
+    pub dr: String,
+    /// The audio health tier of the DR value, e.g. "Good".
+    pub tier: String,
+    pub artist: String,
+    pub album: String,
+    pub year: String,
+    /// The first folder beneath the scan root, which is the Soulseek user when downloads
+    /// are saved in per-user subfolders.
+    pub user: String,
+    /// The current name of the album folder.
+    pub name: String,
+}
+
+/// Returns the file on disk that holds a log: the log itself, or the archive containing it.
+pub fn physical_path(path: &Path) -> PathBuf {
+    split_virtual_path(path).map(|(archive, _)| archive).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Returns the album folder a log belongs to.
+///
+/// This is the folder containing the log (or its archive). Disc subfolders such as
+/// "CD1" or "Disc 2" are skipped, so multi-disc albums resolve to their common folder.
+pub fn album_folder(path: &Path) -> Option<PathBuf> {
+    let parent = physical_path(path).parent()?.to_path_buf();
+    let name = parent.file_name()?.to_string_lossy().to_lowercase();
+    let is_disc_folder = ["cd", "disc", "disk"].iter().any(|prefix| {
+        name.strip_prefix(prefix).is_some_and(|rest| {
+            let rest = rest.trim_start_matches([' ', '_', '-']);
+            !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit())
+        })
+    });
+    if is_disc_folder {
+        parent.parent().map(Path::to_path_buf)
+    } else {
+        Some(parent)
+    }
+}
+
+/// Returns the name of the first folder beneath the scan root that contains `path`.
+pub fn user_folder(path: &Path, roots: &[PathBuf]) -> Option<String> {
+    let physical = physical_path(path);
+    let root = roots.iter().find(|root| root_contains(root, path))?;
+    let relative = physical.strip_prefix(root).ok()?;
+    let mut components = relative.components();
+    let first = components.next()?;
+
+    // A log directly inside the root has no user folder.
+    components.next()?;
+    Some(first.as_os_str().to_string_lossy().into_owned())
+}
+
+/// Collects the template values for a result, reading the log to find its album metadata.
+pub fn template_values(result: &DRResult, roots: &[PathBuf]) -> TemplateValues {
+    let folder = album_folder(&result.path);
+    let metadata = read_metadata(&result.path, folder.as_deref());
+    TemplateValues {
+        dr: result.dr_value.map(|dr| dr.to_string()).unwrap_or_else(|| "ERR".to_string()),
+        tier: result.dr_value.map(|dr| tier_for(dr).1.name).unwrap_or_else(|| "Unknown".to_string()),
+        artist: metadata.artist.unwrap_or_else(|| "Unknown Artist".to_string()),
+        album: metadata.album.unwrap_or_else(|| "Unknown Album".to_string()),
+        year: metadata.year.unwrap_or_default(),
+        user: user_folder(&result.path, roots).unwrap_or_default(),
+        name: folder.and_then(|f| f.file_name().map(|n| n.to_string_lossy().into_owned())).unwrap_or_default(),
+    }
+}
+
+/// Makes a value safe to use inside a single path component.
+fn sanitize(value: &str) -> String {
+    value.chars().map(|c| if c == '/' || c == '\0' { '_' } else { c }).collect()
+}
+
+/// Expands a template such as `{tier}/{artist} - {album}` into a relative path.
+///
+/// Token values never introduce additional path separators; only the slashes written
+/// in the template itself create subfolders. Empty components and components that would
+/// leave the destination (such as `..`) are dropped.
+pub fn expand_template(template: &str, values: &TemplateValues) -> PathBuf {
+    let expanded = template
+        .replace("{dr}", &sanitize(&values.dr))
+        .replace("{tier}", &sanitize(&values.tier))
+        .replace("{artist}", &sanitize(&values.artist))
+        .replace("{album}", &sanitize(&values.album))
+        .replace("{year}", &sanitize(&values.year))
+        .replace("{user}", &sanitize(&values.user))
+        .replace("{name}", &sanitize(&values.name));
+    expanded
+        .split('/')
+        .map(str::trim)
+        .filter(|component| !component.is_empty())
+        .filter(|component| Path::new(component).components().all(|c| matches!(c, Normal(_))))
+        .collect()
+}
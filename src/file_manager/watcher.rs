@@ -0,0 +1,201 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{metadata, read_dir};
+use std::mem::replace;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use gio::{Cancellable, File, FileMonitor, FileMonitorEvent, FileMonitorFlags, ListStore};
+use glib::{timeout_add_local, SourceId};
+use glib::ControlFlow::{Break, Continue};
+use libadwaita::prelude::{FileExt, FileMonitorExt};
+
+use crate::data::app_state::{AppState, ProblemHandler};
+use crate::dr_analyzer::analyzer::{analyze_file, update_rows};
+use crate::file_manager::archive::is_virtual_path;
+use crate::file_manager::file_ops::{find_log_files, is_log_file};
+
+/// How often pending files are checked and the watched roots are synchronized.
+const WATCH_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// The size and modification time of a file at one point in time.
+///
+/// A file is considered fully written once two consecutive snapshots are identical.
+#[derive(Clone, Copy, PartialEq)]
+struct FileSnapshot {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Watches the scan roots for new or changed logs and adds them to the results as they settle.
+///
+/// One `gio::FileMonitor` is kept per directory, so the roots are followed recursively.
+/// Each new or changed `.txt`/`.log` file is held back until its size and modification time
+/// stop changing, which avoids analyzing logs that are still being downloaded. The watched
+/// roots are re-synchronized with `AppState` on every tick, so roots added or removed while
+/// watching are picked up automatically.
+pub struct LogWatcher {
+    app_state: Arc<Mutex<AppState>>,
+    list_store: ListStore,
+    report_problem: ProblemHandler,
+    roots: RefCell<Vec<PathBuf>>,
+    monitors: RefCell<HashMap<PathBuf, FileMonitor>>,
+    pending: RefCell<HashMap<PathBuf, Option<FileSnapshot>>>,
+    timer: RefCell<Option<SourceId>>,
+}
+
+impl LogWatcher {
+
+    /// Starts watching the current scan roots.
+    pub fn start(list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) -> Rc<LogWatcher> {
+        let report_problem = app_state.lock().map(|state| state.report_problem.clone()).unwrap_or_else(|err| err.into_inner().report_problem.clone());
+        let watcher = Rc::new(LogWatcher {
+            app_state: app_state.clone(),
+            list_store: list_store.clone(),
+            report_problem,
+            roots: RefCell::new(Vec::new()),
+            monitors: RefCell::new(HashMap::new()),
+            pending: RefCell::new(HashMap::new()),
+            timer: RefCell::new(None),
+        });
+        watcher.sync_roots();
+        let weak = Rc::downgrade(&watcher);
+        let timer = timeout_add_local(WATCH_INTERVAL, move || {
+            match weak.upgrade() {
+                Some(watcher) => {
+                    watcher.sync_roots();
+                    watcher.process_pending();
+                    Continue
+                }
+                None => Break,
+            }
+        });
+        *watcher.timer.borrow_mut() = Some(timer);
+        watcher
+    }
+
+    /// Stops all directory monitors and discards files that have not settled yet.
+    pub fn stop(&self) {
+        if let Some(timer) = self.timer.borrow_mut().take() {
+            timer.remove();
+        }
+        for (_, monitor) in self.monitors.borrow_mut().drain() {
+            monitor.cancel();
+        }
+        self.pending.borrow_mut().clear();
+        self.roots.borrow_mut().clear();
+    }
+
+    /// Adds monitors for new directory roots and drops monitors of roots that were removed.
+    fn sync_roots(self: &Rc<Self>) {
+        let roots = match self.app_state.try_lock() {
+            Ok(state) => state.roots.clone(),
+            Err(_) => return, // The state is busy; try again on the next tick.
+        };
+        if *self.roots.borrow() == roots {
+            return;
+        }
+        self.monitors.borrow_mut().retain(|dir, monitor| {
+            let keep = roots.iter().any(|root| dir.starts_with(root));
+            if !keep {
+                monitor.cancel();
+            }
+            keep
+        });
+        for root in roots.iter().filter(|root| root.is_dir()) {
+            self.watch_tree(root);
+        }
+        *self.roots.borrow_mut() = roots;
+    }
+
+    /// Recursively adds a monitor for `dir` and all of its subdirectories.
+    fn watch_tree(self: &Rc<Self>, dir: &Path) {
+        if !self.monitors.borrow().contains_key(dir) {
+            match File::for_path(dir).monitor_directory(FileMonitorFlags::WATCH_MOVES, Cancellable::NONE) {
+                Ok(monitor) => {
+                    let weak: Weak<LogWatcher> = Rc::downgrade(self);
+                    monitor.connect_changed(move |_, file, other_file, event| {
+                        if let Some(watcher) = weak.upgrade() {
+                            watcher.handle_event(file, other_file, event);
+                        }
+                    });
+                    self.monitors.borrow_mut().insert(dir.to_path_buf(), monitor);
+                }
+                Err(err) => (self.report_problem)(format!("Failed to watch {}: {}", dir.display(), err)),
+            }
+        }
+        if let Ok(entries) = read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    self.watch_tree(&path);
+                }
+            }
+        }
+    }
+
+    /// Reacts to a change reported by one of the directory monitors.
+    fn handle_event(self: &Rc<Self>, file: &File, other_file: Option<&File>, event: FileMonitorEvent) {
+        let path = match event {
+            FileMonitorEvent::Created | FileMonitorEvent::Changed
+            | FileMonitorEvent::ChangesDoneHint | FileMonitorEvent::MovedIn => file.path(),
+            FileMonitorEvent::Renamed => other_file.and_then(|f| f.path()),
+            _ => None,
+        };
+        let Some(path) = path else {
+            return;
+        };
+        if path.is_dir() {
+
+            // A whole folder was created or moved in: watch it and queue the logs it already contains.
+            self.watch_tree(&path);
+            let mut files = Vec::new();
+            if find_log_files(&path, &mut files, &self.report_problem).is_ok() {
+                let mut pending = self.pending.borrow_mut();
+                for file in files.into_iter().filter(|file| !is_virtual_path(file)) {
+                    pending.insert(file, None);
+                }
+            }
+        } else if is_log_file(&path) {
+
+            // Restart the settle check every time the file changes.
+            self.pending.borrow_mut().insert(path, None);
+        }
+    }
+
+    /// Analyzes every pending file whose size and modification time did not change since the last tick.
+    fn process_pending(&self) {
+        let mut settled = Vec::new();
+        self.pending.borrow_mut().retain(|path, previous| {
+            let current = match metadata(path) {
+                Ok(meta) => FileSnapshot { len: meta.len(), modified: meta.modified().ok() },
+                Err(_) => return false, // The file disappeared before it settled.
+            };
+            if *previous == Some(current) {
+                settled.push(path.clone());
+                false
+            } else {
+                *previous = Some(current);
+                true
+            }
+        });
+        if settled.is_empty() {
+            return;
+        }
+        if let Ok(mut state) = self.app_state.lock() {
+            let (mut removed, mut changed) = (Vec::new(), Vec::new());
+            for path in settled {
+                let mut result = analyze_file(&path);
+                result.is_new = true;
+                changed.push(result.clone());
+                match state.results.iter_mut().find(|existing| existing.path == path) {
+                    Some(existing) => removed.push(replace(existing, result)),
+                    None => state.results.push(result),
+                }
+            }
+            update_rows(&self.list_store, &removed, &changed);
+        }
+    }
+}
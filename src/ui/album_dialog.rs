@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, mpsc::channel};
+use std::thread::spawn;
+use std::time::Duration;
+
+use gio::ListStore;
+use glib::{clone, format_size, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{ScrolledWindow, TextView};
+use libadwaita::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use libadwaita::prelude::{GtkWindowExt, MessageDialogExt, TextBufferExt, TextViewExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::data::settings::current_settings;
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::album_cleanup::{album_folder_target, album_folders, folder_tree, remove_album_folder, remove_empty_parents};
+use crate::file_manager::file_ops::root_contains;
+use crate::file_manager::safety::READ_ONLY_MESSAGE;
+use crate::ui::dialogs::{report_problem_with_details, show_error_dialog};
+
+/// Shows a dry-run preview for removing the album folders of the selected logs at `paths`.
+///
+/// Each selected log is resolved to its album folder (see `album_folder_target`); folders
+/// that are not strictly inside a scan root, or that hold other albums, are refused and listed
+/// as such. The preview shows every file that will be removed and their total size; it is
+/// built on a background thread, and the dialog is shown when it is done. On confirmation, the folders are moved to the trash (or deleted permanently if configured) on
+/// a background thread, empty parent folders are cleaned up up to the scan root, and the
+/// affected results are removed from the list.
+pub fn show_remove_album_dialog(window: &ApplicationWindow, paths: Vec<PathBuf>,
+                                list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    if paths.is_empty() {
+        return;
+    }
+    if current_settings().read_only {
+        show_error_dialog(window, READ_ONLY_MESSAGE);
+        return;
+    }
+    let permanently = current_settings().delete_permanently;
+    let (roots, known) = match app_state.lock() {
+        Ok(state) => (
+            state.roots.clone(),
+            album_folders(state.results.iter().map(|result| result.path.as_path())),
+        ),
+        Err(_) => return,
+    };
+
+    // Resolve the album folders, skipping folders nested inside another selected folder.
+    let mut folders = BTreeSet::new();
+    let mut refused = Vec::new();
+    for path in &paths {
+        match album_folder_target(path, &roots, &known) {
+            Ok(folder) => { folders.insert(folder); }
+            Err(reason) => refused.push(reason),
+        }
+    }
+    let folders: Vec<PathBuf> = folders.iter()
+        .filter(|folder| !folders.iter().any(|other| other != *folder && folder.starts_with(other)))
+        .cloned()
+        .collect();
+
+    // Build the dry-run preview; this walks every folder, so it happens on a background thread.
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let mut preview = AlbumPreview { lines: Vec::new(), file_count: 0, total_size: 0 };
+        for folder in &folders {
+            let tree = folder_tree(folder);
+            preview.file_count += tree.file_count;
+            preview.total_size += tree.total_size;
+            preview.lines.extend(tree.lines);
+            preview.lines.push(String::new());
+        }
+        if !refused.is_empty() {
+            preview.lines.push("Refused:".to_string());
+            preview.lines.extend(refused.iter().map(|reason| format!("    {}", reason)));
+        }
+        done_tx.send((folders, roots, preview)).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok((folders, roots, preview)) = done_rx.try_recv() else {
+            return Continue;
+        };
+        present_remove_album_dialog(&window, folders, roots, permanently, preview, &list_store, &app_state);
+        Break
+    }));
+}
+
+/// The dry-run preview of an album folder removal.
+struct AlbumPreview {
+    /// The folder trees and refused folders, one line each.
+    lines: Vec<String>,
+    file_count: usize,
+    total_size: u64,
+}
+
+/// Asks for confirmation to remove the album `folders`, with the preview below the summary.
+fn present_remove_album_dialog(window: &ApplicationWindow, folders: Vec<PathBuf>, roots: Vec<PathBuf>, permanently: bool,
+                               preview: AlbumPreview, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let summary = format!(
+        "{} {} folder(s) containing {} file(s), {} in total.",
+        if permanently { "This will permanently delete" } else { "This will move to the trash" },
+        folders.len(),
+        preview.file_count,
+        format_size(preview.total_size)
+    );
+    let dialog = MessageDialog::new(Some(window), Some("Remove Album Folders?"), Some(&summary));
+    dialog.set_default_size(700, 500);
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("remove", if permanently { "Delete Permanently" } else { "Move to Trash" });
+    dialog.set_response_appearance("remove", ResponseAppearance::Destructive);
+    dialog.set_response_enabled("remove", !folders.is_empty());
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_monospace(true);
+    text_view.buffer().set_text(&preview.lines.join("\n"));
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_min_content_height(300);
+    scrolled.set_child(Some(&text_view));
+    dialog.set_extra_child(Some(&scrolled));
+
+    dialog.connect_response(None, clone!(@weak window, @strong list_store, @strong app_state => move |_, response| {
+        if response == "remove" {
+            remove_album_folders(&window, folders.clone(), roots.clone(), permanently, &list_store, &app_state);
+        }
+    }));
+    dialog.present();
+}
+
+/// Removes the album folders on a background thread and drops their results from the list.
+pub fn remove_album_folders(window: &ApplicationWindow, folders: Vec<PathBuf>, roots: Vec<PathBuf>, permanently: bool,
+                        list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let mut removed = Vec::new();
+        let mut failures = Vec::new();
+        for folder in folders {
+            match remove_album_folder(&folder, &roots, permanently) {
+                Ok(()) => {
+                    removed.extend(remove_empty_parents(&folder, &roots, permanently));
+                    removed.push(folder);
+                }
+                Err(err) => failures.push(format!("{}: {}", folder.display(), err)),
+            }
+        }
+        done_tx.send((removed, failures)).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok((removed, failures)) = done_rx.try_recv() else {
+            return Continue;
+        };
+        if let Ok(mut state) = app_state.lock() {
+            let removed_results: Vec<DRResult> = state.results
+                .extract_if(.., |result| removed.iter().any(|folder| root_contains(folder, &result.path)))
+                .collect();
+            update_rows(&list_store, &removed_results, &[]);
+        }
+        if !failures.is_empty() {
+            report_problem_with_details(&window, &format!("Could not remove {} folder(s)", failures.len()), failures.join("\n"));
+        }
+        Break
+    }));
+}
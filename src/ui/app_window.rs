@@ -1,112 +1,138 @@
-use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+use std::cell::RefCell;
+use std::rc::Rc;
 
+use gio::{Menu, SimpleAction};
 use glib::clone;
-use gtk4::{Adjustment, Box, Button, Label, Orientation::Vertical, ProgressBar, ScrolledWindow, Viewport};
-use gtk4::Align::{Fill, Start};
-use gtk4::PolicyType::{Automatic, Never};
-use libadwaita::{Application, ApplicationWindow, HeaderBar};
-use libadwaita::prelude::{AdwApplicationWindowExt, BoxExt, ButtonExt, GtkWindowExt, WidgetExt};
-
-use crate::data::app_state::AppState;
-use crate::ui::column_view::create_column_view;
-use crate::ui::header_bar::setup_button_actions;
-use crate::ui::settings_dialog::show_settings_dialog;
-use crate::utils::event_handlers::{setup_keyboard_controls, setup_mouse_controls};
-
-/// Builds the main application window and all its UI components.
+use gtk4::{Box, Button, Orientation::Vertical, ToggleButton};
+use libadwaita::{Application, ApplicationWindow, HeaderBar, TabBar, TabPage, TabView, ToastOverlay, WindowTitle};
+use libadwaita::prelude::{ActionMapExt, ActionableExt, AdwApplicationWindowExt, BoxExt, ButtonExt, GtkApplicationExt, GtkWindowExt, WidgetExt};
+
+use crate::file_manager::journal::set_journal_error_handler;
+use crate::ui::dialogs::{install_toast_overlay, report_problem};
+use crate::ui::header_bar::setup_read_only_action;
+use crate::ui::shortcuts_window::setup_shortcuts_window;
+use crate::ui::workspace::add_workspace;
+use crate::utils::event_handlers::setup_vim_accels;
+
+/// Builds the main application window with one empty workspace.
 ///
-/// This function sets up the `ApplicationWindow`, `HeaderBar`, `ColumnView` for results,
-/// and all associated buttons and event handlers. It initializes the shared application state
-/// and connects all the pieces together.
+/// Each tab of the window is a workspace with its own scan roots, results, filters and scan
+/// progress (see `add_workspace`), so that several collections can be scanned in parallel.
+/// Failures to write the journal are reported as toasts. Read-only mode is an action of the
+/// application, so that it applies to every window and workspace.
 pub fn build_ui(app: &Application) {
+    set_journal_error_handler(report_problem);
+    setup_read_only_action(app);
+    let tab_view = create_window(app);
+    add_workspace(&tab_view);
+}
+
+/// Creates and presents a main window for workspace tabs and returns its `TabView`.
+///
+/// The header bar holds a button for new tabs and the toggle for read-only mode; the `TabBar` below it appears once there is
+/// more than one tab. A tab can be moved into a new window by dragging it out of the tab bar
+/// or from its context menu. A window closes as soon as its last tab is closed or moved away.
+pub fn create_window(app: &Application) -> TabView {
 
     // Create the main application window.
     let window = ApplicationWindow::new(app);
     window.set_title(Some("drlogseeker"));
     window.set_icon_name(Some("com.loxoron218.drlogseeker"));
     window.set_resizable(true);
-    window.set_default_size(1000, 600);
+    window.set_default_size(1000, 800);
 
-    // Create the header bar with action buttons.
+    // Create the header bar with a button for new tabs and the toggle for read-only mode.
     let header_bar = HeaderBar::new();
-    let open_button = Button::from_icon_name("list-add-symbolic");
-    open_button.set_tooltip_text(Some("Select Directory"));    
-    let scan_button = Button::from_icon_name("view-refresh-symbolic");
-    scan_button.set_tooltip_text(Some("Scan Files"));
-    let clear_button = Button::from_icon_name("process-stop-symbolic");
-    clear_button.set_tooltip_text(Some("Clear List"));
-    
-    // Add a settings button with a gear icon.
-    let settings_button = Button::from_icon_name("open-menu-symbolic");
-    settings_button.set_tooltip_text(Some("Settings"));
-    
-    // Buttons start disabled until a directory is selected.
-    scan_button.set_sensitive(false);
-    clear_button.set_sensitive(false);
-    scan_button.add_css_class("suggested-action");
-
-    // Pack buttons into the header bar.
-    header_bar.pack_start(&open_button);
-    header_bar.pack_start(&clear_button);
-    header_bar.pack_end(&settings_button);
-    header_bar.pack_end(&scan_button);
+    let window_title = WindowTitle::new("drlogseeker", "");
+    header_bar.set_title_widget(Some(&window_title));
+    let new_tab_button = Button::from_icon_name("tab-new-symbolic");
+    new_tab_button.set_tooltip_text(Some("New Tab"));
+    new_tab_button.set_action_name(Some("win.new-tab"));
+    header_bar.pack_start(&new_tab_button);
+    let read_only_button = ToggleButton::new();
+    read_only_button.set_icon_name("changes-prevent-symbolic");
+    read_only_button.set_tooltip_text(Some("Read-Only Mode"));
+    read_only_button.set_action_name(Some("app.read-only"));
+    header_bar.pack_end(&read_only_button);
+
+    // Create the tabs, which hold the workspaces.
+    let tab_view = TabView::new();
+    tab_view.set_vexpand(true);
+    let tab_bar = TabBar::new();
+    tab_bar.set_view(Some(&tab_view));
 
     // Create the main vertical layout.
     let vbox = Box::new(Vertical, 0);
     vbox.append(&header_bar);
+    vbox.append(&tab_bar);
+    vbox.append(&tab_view);
+
+    // Problems of the application, such as settings that could not be saved, are reported as
+    // toasts over the content of the active window.
+    let toast_overlay = ToastOverlay::new();
+    toast_overlay.set_child(Some(&vbox));
+    install_toast_overlay(&toast_overlay);
+    window.connect_is_active_notify(clone!(@weak toast_overlay => move |window| {
+        if window.is_active() {
+            install_toast_overlay(&toast_overlay);
+        }
+    }));
+    window.set_content(Some(&toast_overlay));
 
-    // Create the progress bar, initially hidden.
-    let progress_bar = ProgressBar::new();
-    progress_bar.set_visible(false);
-    vbox.append(&progress_bar);
-
-    // Create a scrollable area for the results view.
-    let scrolled = ScrolledWindow::new();
-    scrolled.set_hexpand(true);
-    scrolled.set_vexpand(true);
-    scrolled.set_policy(Never, Automatic);
-    scrolled.set_propagate_natural_height(true);
-    scrolled.set_valign(Fill);
-    scrolled.set_size_request(-1, 400); // Set a minimum height for the scrolled window.
-
-    // Create the column view for displaying results.
-    let (column_view, list_store, selection_model) = create_column_view();
-    let viewport = Viewport::new(None::<&Adjustment>, None::<&Adjustment>);
-    viewport.set_hexpand(true);
-    viewport.set_vexpand(true);
-    viewport.set_valign(Fill);
-    viewport.set_child(Some(&column_view));
-    scrolled.set_child(Some(&viewport));
-    vbox.append(&scrolled);
-
-    // Create a label to display the file count.
-    let file_count_label = Label::new(Some("Files: 0"));
-    file_count_label.set_halign(Start);
-    file_count_label.set_margin_start(10);
-    file_count_label.set_margin_end(10);
-    file_count_label.set_margin_bottom(5);
-    vbox.append(&file_count_label);
-    window.set_content(Some(&vbox));
-
-    // Initialize the shared application state, protected by a Mutex for thread safety.
-    let app_state = Arc::new(Mutex::new(AppState { 
-        results: Vec::new(),
-        delete_files: false,  // Default to not deleting files.
-        delete_folders: false, // Default to not deleting folders.
+    // The context menu of a tab acts on that tab, other shortcuts on the selected tab.
+    let menu = Menu::new();
+    menu.append(Some("Move to New Window"), Some("win.move-tab-to-new-window"));
+    menu.append(Some("Close Tab"), Some("win.close-tab"));
+    tab_view.set_menu_model(Some(&menu));
+    let menu_page: Rc<RefCell<Option<TabPage>>> = Rc::new(RefCell::new(None));
+    tab_view.connect_setup_menu(clone!(@strong menu_page => move |_, page| {
+        *menu_page.borrow_mut() = page.cloned();
     }));
-    let selected_path = Arc::new(Mutex::new(None::<PathBuf>));
+    let target_page = clone!(@weak tab_view, @strong menu_page => @default-return None, move || {
+        menu_page.borrow_mut().take().or_else(|| tab_view.selected_page())
+    });
 
-    // Set up event handlers for keyboard, mouse, and button clicks.
-    setup_keyboard_controls(&window, &selection_model, &list_store, &app_state);
-    setup_mouse_controls(&column_view, &window, &selection_model);
-    setup_button_actions(&window, &open_button, &scan_button, &clear_button, &selected_path, &list_store, &app_state, &progress_bar, &file_count_label);
+    let new_tab_action = SimpleAction::new("new-tab", None);
+    new_tab_action.connect_activate(clone!(@weak tab_view => move |_, _| {
+        add_workspace(&tab_view);
+    }));
+    window.add_action(&new_tab_action);
+
+    let close_tab_action = SimpleAction::new("close-tab", None);
+    close_tab_action.connect_activate(clone!(@weak tab_view, @strong target_page => move |_, _| {
+        if let Some(page) = target_page() {
+            tab_view.close_page(&page);
+        }
+    }));
+    window.add_action(&close_tab_action);
+
+    let move_tab_action = SimpleAction::new("move-tab-to-new-window", None);
+    move_tab_action.connect_activate(clone!(@weak app, @weak tab_view, @strong target_page => move |_, _| {
+        if let Some(page) = target_page() {
+            tab_view.transfer_page(&page, &create_window(&app), 0);
+        }
+    }));
+    window.add_action(&move_tab_action);
+    app.set_accels_for_action("win.new-tab", &["<Control>t"]);
+    app.set_accels_for_action("win.close-tab", &["<Control>w"]);
+
+    // Tabs dragged out of the tab bar open in a new window.
+    tab_view.connect_create_window(clone!(@weak app => @default-return None, move |_| {
+        Some(create_window(&app))
+    }));
 
-    // Connect the settings button to show the settings dialog.
-    settings_button.connect_clicked(clone!(@weak window, @strong app_state => move |_| {
-        show_settings_dialog(&window, &app_state);
+    // Close the window once its last tab has been closed or moved to another window.
+    tab_view.connect_page_detached(clone!(@weak window => move |tab_view, _, _| {
+        if tab_view.n_pages() == 0 {
+            window.close();
+        }
     }));
 
+    // Set up the shortcuts of the window, which act on the selected tab.
+    setup_vim_accels(&window, &tab_view);
+    setup_shortcuts_window(&window);
+
     // Present the window to the user.
     window.present();
+    tab_view
 }
@@ -1,46 +1,198 @@
-use gio::ListStore;
-use gtk4::{Align::Fill, Box, ColumnView, ColumnViewColumn, CssProvider, Label, MultiSelection, Orientation::Horizontal, SignalListItemFactory, StringObject, STYLE_PROVIDER_PRIORITY_APPLICATION};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+use gio::{ListStore, Menu, SimpleAction, SimpleActionGroup};
+use glib::{clone, Object, WeakRef};
+use gtk4::{style_context_add_provider_for_display, Align::Fill, Box, ColumnView, ColumnViewColumn, CssProvider, CustomSorter, Filter, FilterListModel, GestureClick, Label, ListItem, ListItemFactory, MultiSelection, Orientation::Horizontal, SignalListItemFactory, SortListModel, StringObject, STYLE_PROVIDER_PRIORITY_APPLICATION};
 use gtk4::pango::EllipsizeMode::End;
-use libadwaita::prelude::{BoxExt, CastNone, ListItemExt, StyleContextExt, WidgetExt};
+use gtk4::gdk::Display;
+use libadwaita::prelude::{ActionMapExt, BoxExt, EventControllerExt, GestureSingleExt, Cast, CastNone, ListItemExt, ListModelExt, ObjectExt, ToVariant, WidgetExt};
+
+use crate::data::tiers::{current_tiers, tier_index};
+use crate::ui::dialogs::report_problem;
+use crate::utils::config::{load_key_file, save_key_file};
 
-use crate::utils::constants::DR_COLORS;
+/// The file in the config directory that remembers the column layout.
+const COLUMN_LAYOUT_FILE: &str = "columns.ini";
 
 /// Creates and configures a `ColumnView` for displaying file analysis results.
 ///
 /// This function initializes a three-column layout:
-/// 1. File Name (fixed width)
+/// 1. File Name
 /// 2. Full Path (expandable)
-/// 3. DR Value (fixed width)
+/// 3. DR Value
+///
+/// Clicking a column header sorts by that column in ascending or descending order. The
+/// column clicked before serves as secondary sort key, and the path breaks remaining ties.
+/// Until a column is clicked, the rows keep the order of `update_ui`. Columns can be resized
+/// and reordered, and hidden through the menu on their headers. The layout is restored from
+/// the config directory; see `save_column_layout`.
 ///
-/// This setup ensures that the "Path" column is the only one that grows or shrinks
-/// with the window size, preventing horizontal scrolling. User resizing of columns
-/// is disabled to maintain a stable layout.
-pub fn create_column_view() -> (ColumnView, ListStore, MultiSelection) {
+/// The rows pass through a `FilterListModel` before they are sorted; it has no filter
+/// until the search bar sets one (see `create_search_bar`).
+pub fn create_column_view() -> (ColumnView, ListStore, MultiSelection, FilterListModel) {
     let list_store = ListStore::new::<StringObject>();
-    let selection_model = MultiSelection::new(Some(list_store.clone()));
-    let column_view = ColumnView::new(Some(selection_model.clone()));
+    let column_view = ColumnView::new(None::<MultiSelection>);
+    let filter_model = FilterListModel::new(Some(list_store.clone()), None::<Filter>);
+    let sort_model = SortListModel::new(Some(filter_model.clone()), column_view.sorter());
+    let selection_model = MultiSelection::new(Some(sort_model));
+    column_view.set_model(Some(&selection_model));
     
     // Configure the appearance and behavior of the column view.
     column_view.set_show_row_separators(true);
     column_view.set_enable_rubberband(true);
+    column_view.set_reorderable(true);
     column_view.set_hexpand(true);
     column_view.set_vexpand(true);
     column_view.set_valign(Fill);
 
-    // Add the columns to the view.
-    add_column(&column_view, "File Name", Some(250), false, |text| text.split('\t').next().unwrap_or(""));
-    add_column(&column_view, "Path", None, true, |text| text.split('\t').nth(1).unwrap_or(""));
-    add_dr_column(&column_view);
+    // Add the columns to the view, each with its sort key.
+    let filename_column = add_filename_column(&column_view);
+    filename_column.set_sorter(Some(&create_sorter(|text| row_field(text, 0).to_lowercase())));
+    let path_column = add_column(&column_view, "Path", None, true, |text| text.split('\t').nth(1).unwrap_or(""));
+    path_column.set_sorter(Some(&create_sorter(|text| row_field(text, 1).to_string())));
+    let dr_column = add_dr_column(&column_view);
+    dr_column.set_sorter(Some(&create_sorter(dr_sort_key)));
+    DR_COLUMN_VIEWS.with_borrow_mut(|column_views| column_views.push(column_view.downgrade()));
+
+    install_dr_color_css();
+    restore_column_layout(&column_view);
+    setup_column_chooser(&column_view);
+
+    (column_view, list_store, selection_model, filter_model)
+}
+
+/// Returns the tab-separated field `index` of a row's text.
+pub fn row_field(text: &str, index: usize) -> &str {
+    text.split('\t').nth(index).unwrap_or("")
+}
 
-    (column_view, list_store, selection_model)
+/// Orders DR values numerically, with `ERR` and `INVALID` below every value and `PENDING` below them.
+fn dr_sort_key(text: &str) -> i32 {
+    match row_field(text, 2) {
+        "PENDING" => -2,
+        dr => dr.parse().unwrap_or(-1),
+    }
+}
+
+/// Creates a sorter that compares rows by `key`, and by path if the keys are equal.
+fn create_sorter<K: Ord>(key: impl Fn(&str) -> K + 'static) -> CustomSorter {
+    CustomSorter::new(move |a: &Object, b: &Object| {
+        let (Some(a), Some(b)) = (a.downcast_ref::<StringObject>(), b.downcast_ref::<StringObject>()) else {
+            return Ordering::Equal.into();
+        };
+        let (a, b) = (a.string(), b.string());
+        key(&a).cmp(&key(&b))
+            .then_with(|| row_field(&a, 1).cmp(row_field(&b, 1)))
+            .into()
+    })
+}
+
+/// Returns the name of the column chooser action for a column title, e.g. "show-dr-value".
+fn column_action_name(title: &str) -> String {
+    format!("show-{}", title.to_lowercase().replace(' ', "-"))
+}
+
+/// Adds a menu to every column header that shows or hides the columns.
+///
+/// Each column has a stateful action in the `columns` action group of the view. The last
+/// visible column cannot be hidden.
+fn setup_column_chooser(column_view: &ColumnView) {
+    let actions = SimpleActionGroup::new();
+    let menu = Menu::new();
+    let columns = column_view.columns();
+    for column in (0..columns.n_items()).filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>()) {
+        let title = column.title().map(|t| t.to_string()).unwrap_or_default();
+        let name = column_action_name(&title);
+        let action = SimpleAction::new_stateful(&name, None, &column.is_visible().to_variant());
+        action.connect_activate(clone!(@weak column, @weak column_view => move |action, _| {
+            let visible = !column.is_visible();
+            let columns = column_view.columns();
+            let visible_count = (0..columns.n_items())
+                .filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>())
+                .filter(|c| c.is_visible())
+                .count();
+            if !visible && visible_count <= 1 {
+                return; // Keep at least one column.
+            }
+            column.set_visible(visible);
+            action.set_state(&visible.to_variant());
+        }));
+        actions.add_action(&action);
+        menu.append(Some(&title), Some(&format!("columns.{}", name)));
+    }
+    column_view.insert_action_group("columns", Some(&actions));
+    for column in (0..columns.n_items()).filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>()) {
+        column.set_header_menu(Some(&menu));
+    }
+}
+
+/// Applies the column order, widths and visibility saved by `save_column_layout`.
+fn restore_column_layout(column_view: &ColumnView) {
+    let key_file = load_key_file(COLUMN_LAYOUT_FILE);
+    let columns = column_view.columns();
+    let mut placed: Vec<(i32, ColumnViewColumn)> = (0..columns.n_items())
+        .filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>())
+        .filter_map(|column| {
+            let title = column.title()?.to_string();
+            if let Ok(width) = key_file.integer(&title, "width") {
+                column.set_fixed_width(width);
+            }
+            if let Ok(visible) = key_file.boolean(&title, "visible") {
+                column.set_visible(visible);
+            }
+            key_file.integer(&title, "position").ok().map(|position| (position, column))
+        })
+        .collect();
+    placed.sort_by_key(|(position, _)| *position);
+    for (position, column) in placed {
+        column_view.insert_column(position.clamp(0, columns.n_items() as i32 - 1) as u32, &column);
+    }
+}
+
+/// Saves the column order, widths and visibility to the config directory.
+///
+/// This is called when the main window is closed.
+pub fn save_column_layout(column_view: &ColumnView) {
+    let key_file = load_key_file(COLUMN_LAYOUT_FILE);
+    let columns = column_view.columns();
+    for (position, column) in (0..columns.n_items()).filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>()).enumerate() {
+        let Some(title) = column.title() else {
+            continue;
+        };
+        key_file.set_integer(&title, "position", position as i32);
+        key_file.set_integer(&title, "width", column.fixed_width());
+        key_file.set_boolean(&title, "visible", column.is_visible());
+    }
+    if let Err(err) = save_key_file(COLUMN_LAYOUT_FILE, &key_file) {
+        report_problem(err);
+    }
+}
+
+/// Selects the row of a cell when it is right-clicked, so the context menu acts on that row.
+///
+/// Rows that are already selected keep the whole selection. The row is selected through the
+/// "row.select" action of the `ColumnView` (see `setup_row_menu`).
+pub fn select_on_right_click(list_item: &ListItem) {
+    let Some(child) = list_item.child() else {
+        return;
+    };
+    let gesture_click = GestureClick::new();
+    gesture_click.set_button(3); // Secondary (right) mouse button.
+    gesture_click.connect_pressed(clone!(@weak list_item => move |gesture, _n_press, _x, _y| {
+        if !list_item.is_selected() {
+            let _ = gesture.widget().activate_action("row.select", Some(&list_item.position().to_variant()));
+        }
+    }));
+    child.add_controller(gesture_click);
 }
 
 /// Adds a generic text column to the `ColumnView`.
 ///
 /// This helper function creates a column with a specified `title` and uses a
 /// `text_extractor` closure to determine which part of the row's data string to display.
-/// The cell's label is configured to ellipsize long text.
-pub fn add_column(column_view: &ColumnView, title: &str, fixed_width: Option<i32>, expand: bool, text_extractor: impl Fn(&str) -> &str + 'static) {
+/// The cell's label is configured to ellipsize long text. Returns the new column.
+pub fn add_column(column_view: &ColumnView, title: &str, fixed_width: Option<i32>, expand: bool, text_extractor: impl Fn(&str) -> &str + 'static) -> ColumnViewColumn {
     let factory = SignalListItemFactory::new();
     
     // The setup handler creates the label widget for the cell.
@@ -51,6 +203,7 @@ pub fn add_column(column_view: &ColumnView, title: &str, fixed_width: Option<i32
         label.set_margin_end(5);
         label.set_ellipsize(End); // Truncate long text.
         list_item.set_child(Some(&label));
+        select_on_right_click(list_item);
     });
 
     // The bind handler updates the label's text when the cell is bound to data.
@@ -62,75 +215,197 @@ pub fn add_column(column_view: &ColumnView, title: &str, fixed_width: Option<i32
     });
 
     let column = ColumnViewColumn::new(Some(title), Some(factory));
-    column.set_resizable(false); // Disable user resizing for this column.
+    column.set_resizable(true);
     column.set_expand(expand);
     if let Some(width) = fixed_width {
         column.set_fixed_width(width);
     }
     column_view.append_column(&column);
+    column
+}
+
+/// Adds the "File Name" column to the `ColumnView`.
+///
+/// Next to the file name, a small "NEW" badge is shown for logs that watch mode
+/// added since the last scan, and a "MARKED" badge for logs marked by a retention policy.
+/// Returns the new column.
+pub fn add_filename_column(column_view: &ColumnView) -> ColumnViewColumn {
+    let factory = SignalListItemFactory::new();
+
+    // The setup handler creates the file name label followed by the (hidden) badges.
+    factory.connect_setup(move |_, list_item| {
+        let hbox = Box::new(Horizontal, 5);
+        hbox.set_margin_start(5);
+        hbox.set_margin_end(5);
+        let label = Label::new(None);
+        label.set_xalign(0.0); // Left-align text.
+        label.set_hexpand(true);
+        label.set_ellipsize(End); // Truncate long text.
+        let badge = Label::new(Some("NEW"));
+        badge.add_css_class("caption-heading");
+        badge.add_css_class("accent");
+        badge.set_visible(false);
+        let marked_badge = Label::new(Some("MARKED"));
+        marked_badge.add_css_class("caption-heading");
+        marked_badge.add_css_class("warning");
+        marked_badge.set_visible(false);
+        hbox.append(&label);
+        hbox.append(&badge);
+        hbox.append(&marked_badge);
+        list_item.set_child(Some(&hbox));
+        select_on_right_click(list_item);
+    });
+
+    // The bind handler updates the file name and toggles the badges.
+    factory.connect_bind(move |_, list_item| {
+        let string_object = list_item.item().and_downcast::<StringObject>().unwrap();
+        let hbox = list_item.child().and_downcast::<Box>().unwrap();
+        let label = hbox.first_child().and_downcast::<Label>().unwrap();
+        let badge = label.next_sibling().and_downcast::<Label>().unwrap();
+        let marked_badge = hbox.last_child().and_downcast::<Label>().unwrap();
+        let text = string_object.string();
+        let mut fields = text.split('\t');
+        label.set_text(fields.next().unwrap_or(""));
+        badge.set_visible(fields.nth(2) == Some("new"));
+        marked_badge.set_visible(fields.next() == Some("marked"));
+    });
+
+    let column = ColumnViewColumn::new(Some("File Name"), Some(factory));
+    column.set_resizable(true);
+    column.set_expand(false);
+    column.set_fixed_width(250);
+    column_view.append_column(&column);
+    column
 }
 
 /// Adds the specialized "DR Value" column to the `ColumnView`.
 ///
-/// This column displays the DR value and a colored box next to it. The color of the box
-/// - `PENDING` is gray.                                                                                                                                                   │                                                                                                                                       │
-/// - `ERR` is dark gray.                                                                                                                                                  │                                                                                                                                                                   │
-/// - Numeric values are colored based on the `DR_COLORS` constant.
-/// is determined by the DR value, providing a quick visual indicator of the audio quality.
-pub fn add_dr_column(column_view: &ColumnView) {
+/// This column displays the DR value with a colored box before it and the name of its DR
+/// tier after it. The color of the box provides a quick visual indicator of the audio quality:
+/// - `PENDING` is gray.
+/// - `ERR` and `INVALID` are dark gray.
+/// - Numeric values have the color of their tier (see `current_tiers`).
+///
+/// The colors are applied through the CSS classes of `load_dr_color_css`, and the class and
+/// tier name of every DR value are cached along with them, so binding a row only looks them
+/// up and swaps a class. Returns the new column.
+pub fn add_dr_column(column_view: &ColumnView) -> ColumnViewColumn {
     let factory = SignalListItemFactory::new();
     
-    // The setup handler creates a horizontal box containing the color indicator and the label.
+    // The setup handler creates a horizontal box containing the color indicator and the labels.
     factory.connect_setup(move |_, list_item| {
         let hbox = Box::new(Horizontal, 5);
         let color_box = Box::new(Horizontal, 0);
         let label = Label::new(None);
+        let tier_label = Label::new(None);
         
         color_box.set_size_request(16, 16);
         color_box.add_css_class("color-box");
+        tier_label.add_css_class("dim-label");
+        tier_label.set_ellipsize(End);
         
         hbox.append(&color_box);
         hbox.append(&label);
+        hbox.append(&tier_label);
         hbox.set_spacing(5);
         list_item.set_child(Some(&hbox));
+        select_on_right_click(list_item);
     });
 
-    // The bind handler updates the label text and the color of the indicator box.
+    // The bind handler updates the labels and the color class of the indicator box.
     factory.connect_bind(move |_, list_item| {
         let string_object = list_item.item().and_downcast::<StringObject>().unwrap();
         let hbox = list_item.child().and_downcast::<Box>().unwrap();
         let color_box = hbox.first_child().and_downcast::<Box>().unwrap();
-        let label = hbox.last_child().and_downcast::<Label>().unwrap();
+        let label = color_box.next_sibling().and_downcast::<Label>().unwrap();
+        let tier_label = hbox.last_child().and_downcast::<Label>().unwrap();
 
         let text = string_object.string();
         let dr_text = text.split('\t').nth(2).unwrap_or("PENDING");
         label.set_text(dr_text);
-        
-        // Determine the color based on the DR text.
-        let (r, g, b) = match dr_text {
-            "PENDING" => (180, 180, 180),
-            "ERR" => (128, 128, 128),
-            _ => dr_text.parse::<u8>()
-                .map(|dr| {
-                    if dr >= DR_COLORS.len() as u8 { // Handle DR values outside the defined color range.
-                        DR_COLORS[DR_COLORS.len() - 1]
-                    } else {
-                        DR_COLORS[dr as usize]
-                    }
-                })
-                .unwrap_or((128, 128, 128)) // Default to error color on parse failure.
-        };
-        
-        // Apply the color to the indicator box using a dynamic CSS provider.
-        let css = format!("box.color-box {{ background-color: rgb({}, {}, {}); }}", r, g, b);
-        let css_provider = CssProvider::new();
-        css_provider.load_from_data(&css);
-        color_box.style_context().add_provider(&css_provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
+        match dr_text.parse::<u8>() {
+            Ok(dr) => DR_TIER_CELLS.with_borrow(|cells| {
+                let (css_class, tier_name) = &cells[dr as usize];
+                color_box.set_css_classes(&["color-box", css_class]);
+                tier_label.set_text(tier_name);
+            }),
+            Err(_) => {
+
+                // `ERR`, `INVALID` and unparsable values are shown like errors.
+                color_box.set_css_classes(&["color-box", if dr_text == "PENDING" { "dr-pending" } else { "dr-error" }]);
+                tier_label.set_text("");
+            }
+        }
     });
 
     let column = ColumnViewColumn::new(Some("DR Value"), Some(factory));
-    column.set_resizable(false); // Disable user resizing for this column.
+    column.set_resizable(true);
     column.set_expand(false);
-    column.set_fixed_width(120);
+    column.set_fixed_width(200);
     column_view.append_column(&column);
-}
\ No newline at end of file
+    column
+}
+
+/// Applies changed DR tiers to the results views of all tabs and windows.
+///
+/// The tier colors are reloaded once, and the cells of every DR column are created anew, so
+/// that every row picks up the class and name of its new tier. The selections are kept.
+pub fn refresh_dr_columns() {
+    load_dr_color_css();
+    DR_COLUMN_VIEWS.with_borrow_mut(|column_views| {
+        column_views.retain(|column_view| column_view.upgrade().is_some());
+        for column_view in column_views.iter().filter_map(WeakRef::upgrade) {
+            let columns = column_view.columns();
+            for column in (0..columns.n_items()).filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>()) {
+                if column.title().as_deref() == Some("DR Value") {
+                    let factory = column.factory();
+                    column.set_factory(None::<&ListItemFactory>);
+                    column.set_factory(factory.as_ref());
+                }
+            }
+        }
+    });
+}
+
+thread_local! {
+    /// The style provider with one CSS class per DR tier, shared by all rows.
+    static DR_COLOR_CSS: CssProvider = CssProvider::new();
+
+    /// The CSS class and the tier name of every DR value, indexed by the value.
+    static DR_TIER_CELLS: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// The results views of all tabs, whose DR columns follow changes of the tiers.
+    static DR_COLUMN_VIEWS: RefCell<Vec<WeakRef<ColumnView>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Registers the CSS classes of the DR column for the whole display.
+fn install_dr_color_css() {
+    let Some(display) = Display::default() else {
+        return;
+    };
+    DR_COLOR_CSS.with(|css_provider| {
+        style_context_add_provider_for_display(&display, css_provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
+    });
+    load_dr_color_css();
+}
+
+/// Generates one CSS class per DR tier, plus the classes for pending and failed logs.
+///
+/// The classes are generated once per change of the tiers, instead of creating a style
+/// provider for every row that is bound, which slowed down scrolling through large scans.
+/// The class and tier name of every DR value are cached in `DR_TIER_CELLS` at the same time.
+fn load_dr_color_css() {
+    let tiers = current_tiers();
+    let mut css = String::from("box.color-box.dr-pending { background-color: rgb(180, 180, 180); }\n\
+        box.color-box.dr-error { background-color: rgb(128, 128, 128); }\n");
+    for (index, tier) in tiers.iter().enumerate() {
+        let (r, g, b) = tier.color;
+        css.push_str(&format!("box.color-box.dr-tier-{} {{ background-color: rgb({}, {}, {}); }}\n", index, r, g, b));
+    }
+    DR_COLOR_CSS.with(|css_provider| css_provider.load_from_data(&css));
+    let cells = (0..=u8::MAX).map(|dr| {
+        let index = tier_index(&tiers, dr);
+        (format!("dr-tier-{}", index), tiers[index].name.clone())
+    }).collect();
+    DR_TIER_CELLS.set(cells);
+}
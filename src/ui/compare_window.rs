@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+use std::time::Duration;
+
+use glib::{clone, markup_escape_text, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{Box, Grid, Label, MultiSelection, Orientation::Vertical, ScrolledWindow, Window};
+use gtk4::pango::EllipsizeMode::Middle;
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{BoxExt, GridExt, GtkWindowExt, WidgetExt};
+
+use crate::dr_analyzer::analyzer::analyze_file;
+use crate::dr_analyzer::comparison::{align_tracks, TrackPair};
+use crate::dr_analyzer::log_parser::{parse_tracks, read_log_text, TrackEntry};
+use crate::file_manager::file_ops::selected_paths;
+use crate::ui::detail_pane::dr_markup;
+use crate::ui::dialogs::show_error_dialog;
+
+/// A log that takes part in a comparison, read on a background thread.
+struct ComparedLog {
+    path: PathBuf,
+    dr_value: Option<u8>,
+    tracks: Option<Vec<TrackEntry>>,
+}
+
+/// Shows the two selected logs side by side.
+///
+/// The per-track tables of log A and log B are aligned by track number and title (see
+/// `align_tracks`), and the DR, peak and RMS of each track are shown with the difference
+/// of B to A. Tracks on which one master is more compressed than the other are highlighted,
+/// and a summary counts them for both logs. Exactly two rows must be selected.
+pub fn show_compare_window(window: &ApplicationWindow, selection_model: &MultiSelection) {
+    let paths = selected_paths(selection_model);
+    let [left, right] = paths.as_slice() else {
+        show_error_dialog(window, "Select exactly two logs to compare.");
+        return;
+    };
+    let (done_tx, done_rx) = channel();
+    let (left, right) = (left.clone(), right.clone());
+    spawn(move || {
+        let read = |path: PathBuf| ComparedLog {
+            dr_value: analyze_file(&path).dr_value,
+            tracks: read_log_text(&path).map(|content| parse_tracks(&content)),
+            path,
+        };
+        let _ = done_tx.send((read(left), read(right)));
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window => @default-return Break, move || {
+        let Ok((left, right)) = done_rx.try_recv() else {
+            return Continue;
+        };
+        for log in [&left, &right] {
+            if log.tracks.is_none() {
+                show_error_dialog(&window, &format!("Could not read {}.", log.path.display()));
+                return Break;
+            }
+        }
+        present_comparison(&window, &left, &right);
+        Break
+    }));
+}
+
+/// Builds and presents the comparison window for two logs that were read successfully.
+fn present_comparison(window: &ApplicationWindow, left: &ComparedLog, right: &ComparedLog) {
+    let pairs = align_tracks(left.tracks.as_deref().unwrap_or_default(), right.tracks.as_deref().unwrap_or_default());
+
+    let compare = Window::new();
+    compare.set_title(Some("Compare Logs"));
+    compare.set_transient_for(Some(window));
+    compare.set_default_size(1000, 500);
+
+    let vbox = Box::new(Vertical, 10);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+
+    // Name both logs with their official DR.
+    for (name, log) in [("A", left), ("B", right)] {
+        let label = Label::new(None);
+        label.set_markup(&format!("<b>{}</b>  {}  {}",
+            name,
+            log.dr_value.map(|dr| dr_markup(Some(dr))).unwrap_or_else(|| "No DR".to_string()),
+            markup_escape_text(&log.path.display().to_string())
+        ));
+        label.set_xalign(0.0);
+        label.set_ellipsize(Middle);
+        label.set_tooltip_text(Some(&log.path.display().to_string()));
+        vbox.append(&label);
+    }
+
+    // Count the tracks on which each master is more compressed.
+    let left_worse = pairs.iter().filter(|pair| pair.dr_delta().is_some_and(|delta| delta > 0)).count();
+    let right_worse = pairs.iter().filter(|pair| pair.dr_delta().is_some_and(|delta| delta < 0)).count();
+    let unpaired = pairs.iter().filter(|pair| pair.left.is_none() || pair.right.is_none()).count();
+    let summary = Label::new(Some(&format!(
+        "A is more compressed on {} track(s), B on {} track(s). {} track(s) appear in only one log.",
+        left_worse, right_worse, unpaired
+    )));
+    summary.set_xalign(0.0);
+    summary.add_css_class("heading");
+    vbox.append(&summary);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(5);
+    grid.set_column_spacing(15);
+    let headers = ["Track", "DR A", "DR B", "Δ DR", "Peak A", "Peak B", "Δ Peak", "RMS A", "RMS B", "Δ RMS", ""];
+    for (column, title) in headers.iter().enumerate() {
+        let header = Label::new(Some(title));
+        header.set_xalign(0.0);
+        header.add_css_class("heading");
+        grid.attach(&header, column as i32, 0, 1, 1);
+    }
+    if pairs.is_empty() {
+        let label = Label::new(Some("Neither log has a per-track table."));
+        label.add_css_class("dim-label");
+        grid.attach(&label, 0, 1, headers.len() as i32, 1);
+    }
+    for (row, pair) in pairs.iter().enumerate() {
+        attach_pair_row(&grid, row as i32 + 1, pair);
+    }
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&grid));
+    vbox.append(&scrolled);
+
+    compare.set_child(Some(&vbox));
+    compare.present();
+}
+
+/// Adds the row of a track pair to the comparison grid.
+fn attach_pair_row(grid: &Grid, row: i32, pair: &TrackPair) {
+    let title = pair.left.as_ref().or(pair.right.as_ref()).map(|track| track.title.as_str()).unwrap_or("");
+    let title_label = Label::new(Some(title));
+    title_label.set_xalign(0.0);
+    title_label.set_ellipsize(Middle);
+    title_label.set_max_width_chars(40);
+    title_label.set_tooltip_text(Some(title));
+    grid.attach(&title_label, 0, row, 1, 1);
+
+    let dr_label = |track: Option<&TrackEntry>| {
+        let label = Label::new(None);
+        match track {
+            Some(track) => label.set_markup(&dr_markup(track.dr)),
+            None => label.set_text("—"),
+        }
+        label.set_xalign(0.0);
+        label
+    };
+    let level_label = |text: Option<&str>| {
+        let label = Label::new(Some(text.unwrap_or("—")));
+        label.set_xalign(1.0);
+        label
+    };
+    let delta_label = |delta: Option<String>| {
+        let label = Label::new(Some(&delta.unwrap_or_default()));
+        label.set_xalign(1.0);
+        label
+    };
+    let (left, right) = (pair.left.as_ref(), pair.right.as_ref());
+    let cells = [
+        dr_label(left),
+        dr_label(right),
+        delta_label(pair.dr_delta().map(|delta| format!("{:+}", delta))),
+        level_label(left.map(|track| track.peak.as_str())),
+        level_label(right.map(|track| track.peak.as_str())),
+        delta_label(pair.peak_delta().map(|delta| format!("{:+.2} dB", delta))),
+        level_label(left.map(|track| track.rms.as_str())),
+        level_label(right.map(|track| track.rms.as_str())),
+        delta_label(pair.rms_delta().map(|delta| format!("{:+.2} dB", delta))),
+    ];
+
+    // Highlight the master that is more compressed on this track.
+    let verdict = Label::new(None);
+    verdict.set_xalign(0.0);
+    match pair.dr_delta() {
+        Some(delta) if delta != 0 => {
+            let (worse, index) = if delta > 0 { ("A", 0) } else { ("B", 1) };
+            verdict.set_text(&format!("{} more compressed", worse));
+            verdict.add_css_class("warning");
+            cells[index].add_css_class("heading");
+            cells[2].add_css_class("warning");
+            title_label.add_css_class("warning");
+        }
+        _ if left.is_none() || right.is_none() => {
+            verdict.set_text(&format!("Only in {}", if left.is_some() { "A" } else { "B" }));
+            verdict.add_css_class("dim-label");
+        }
+        _ => {}
+    }
+    for (column, cell) in cells.iter().enumerate() {
+        grid.attach(cell, column as i32 + 1, row, 1, 1);
+    }
+    grid.attach(&verdict, cells.len() as i32 + 1, row, 1, 1);
+}
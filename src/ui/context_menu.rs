@@ -0,0 +1,260 @@
+use std::path::{Path, PathBuf};
+use std::mem::replace;
+use std::process::Command;
+use std::slice::from_ref;
+use std::sync::{Arc, Mutex, mpsc::channel};
+use std::thread::spawn;
+use std::time::Duration;
+
+use gio::{bus_get_sync, AppLaunchContext, BusType, Cancellable, DBusCallFlags, File, ListStore, Menu, SimpleAction, SimpleActionGroup};
+use gio::prelude::FileExt;
+use glib::{clone, timeout_add_local, VariantTy};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{AppChooserDialog, ColumnView, DialogFlags, EventSequenceState::Claimed, GestureClick, MultiSelection, PopoverMenu, StringObject};
+use gtk4::gdk::Rectangle;
+use gtk4::ResponseType::Ok as ResponseOk;
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{ActionMapExt, AppChooserExt, AppInfoExt, Cast, DialogExt, GestureExt, GestureSingleExt, GtkWindowExt, ListModelExt, PopoverExt, SelectionModelExt, ToVariant, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::dr_analyzer::analyzer::{analyze_file, update_rows};
+use crate::file_manager::archive::{extract_to_temp, is_virtual_path};
+use crate::file_manager::file_ops::{delete_selected_files, selected_paths, try_open_file};
+use crate::file_manager::templates::physical_path;
+use crate::ui::compare_window::show_compare_window;
+use crate::ui::dialogs::show_error_dialog;
+use crate::ui::workspace::workspace_window;
+
+/// Sets up the context menu of the results view, opened with the secondary mouse button.
+///
+/// The menu entries are `GAction`s in the "row" action group of the `ColumnView` and act on
+/// all selected rows: Open, Open With…, Show in Folder, Compare (for two rows), Copy Path,
+/// Copy DR Summary, Rescan, Remove from List and Delete. Right-clicking a row that is not selected selects it first
+/// (see `select_on_right_click` in `column_view.rs`), so the menu always acts on what the
+/// user pointed at.
+pub fn setup_context_menu(column_view: &ColumnView, selection_model: &MultiSelection,
+                          list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let actions = SimpleActionGroup::new();
+    add_action(&actions, "open", clone!(@weak column_view, @weak selection_model => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        for path in selected_paths(&selection_model) {
+            try_open_file(&window, &path);
+        }
+    }));
+    add_action(&actions, "open-with", clone!(@weak column_view, @weak selection_model => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        if let Some(path) = selected_paths(&selection_model).first() {
+            open_with(&window, path);
+        }
+    }));
+    add_action(&actions, "show-in-folder", clone!(@weak column_view, @weak selection_model => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        show_in_folder(&window, &selected_paths(&selection_model));
+    }));
+    add_action(&actions, "compare", clone!(@weak column_view, @weak selection_model => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        show_compare_window(&window, &selection_model);
+    }));
+    add_action(&actions, "copy-path", clone!(@weak column_view, @weak selection_model => move || {
+        let paths: Vec<String> = selected_paths(&selection_model).iter().map(|path| path.display().to_string()).collect();
+        column_view.clipboard().set_text(&paths.join("\n"));
+    }));
+    add_action(&actions, "copy-summary", clone!(@weak column_view, @weak selection_model => move || {
+        let lines: Vec<String> = selected_rows(&selection_model).iter().map(|row| {
+            let fields: Vec<&str> = row.split('\t').collect();
+            let dr = fields.get(2).copied().unwrap_or("PENDING");
+            let dr = if dr.parse::<u8>().is_ok() { format!("DR{}", dr) } else { dr.to_string() };
+            format!("{}\t{}\t{}", fields.first().copied().unwrap_or(""), dr, fields.get(1).copied().unwrap_or(""))
+        }).collect();
+        column_view.clipboard().set_text(&lines.join("\n"));
+    }));
+    add_action(&actions, "rescan", clone!(@weak selection_model, @weak list_store, @strong app_state => move || {
+        rescan_paths(selected_paths(&selection_model), &list_store, &app_state);
+    }));
+    add_action(&actions, "remove-from-list", clone!(@weak selection_model, @weak list_store, @strong app_state => move || {
+        let paths = selected_paths(&selection_model);
+        if let Ok(mut state) = app_state.lock() {
+            let removed: Vec<DRResult> = state.results.extract_if(.., |result| paths.contains(&result.path)).collect();
+            update_rows(&list_store, &removed, &[]);
+        }
+    }));
+    add_action(&actions, "delete", clone!(@weak column_view, @weak selection_model, @weak list_store, @strong app_state => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        delete_selected_files(&window, &selection_model, &list_store, &app_state);
+    }));
+
+    let menu = Menu::new();
+    let open_section = Menu::new();
+    open_section.append(Some("Open"), Some("row.open"));
+    open_section.append(Some("Open With…"), Some("row.open-with"));
+    open_section.append(Some("Show in Folder"), Some("row.show-in-folder"));
+    menu.append_section(None, &open_section);
+    let compare_section = Menu::new();
+    compare_section.append(Some("Compare"), Some("row.compare"));
+    menu.append_section(None, &compare_section);
+    let copy_section = Menu::new();
+    copy_section.append(Some("Copy Path"), Some("row.copy-path"));
+    copy_section.append(Some("Copy DR Summary"), Some("row.copy-summary"));
+    menu.append_section(None, &copy_section);
+    let list_section = Menu::new();
+    list_section.append(Some("Rescan"), Some("row.rescan"));
+    list_section.append(Some("Remove from List"), Some("row.remove-from-list"));
+    list_section.append(Some("Delete"), Some("row.delete"));
+    menu.append_section(None, &list_section);
+    setup_row_menu(column_view, selection_model, &actions, &menu);
+}
+
+/// Shows `menu` as the context menu of the rows of a `ColumnView`.
+///
+/// `actions` become the "row" action group of the view, together with a "select" action that
+/// selects a single row by position. The cells activate it when they are right-clicked (see
+/// `select_on_right_click` in `column_view.rs`), so the menu only has to be shown here.
+pub fn setup_row_menu(column_view: &ColumnView, selection_model: &MultiSelection, actions: &SimpleActionGroup, menu: &Menu) {
+    let select_action = SimpleAction::new("select", Some(VariantTy::UINT32));
+    select_action.connect_activate(clone!(@weak selection_model => move |_, parameter| {
+        if let Some(position) = parameter.and_then(|parameter| parameter.get::<u32>()) {
+            selection_model.select_item(position, true);
+        }
+    }));
+    actions.add_action(&select_action);
+    column_view.insert_action_group("row", Some(actions));
+
+    let popover = PopoverMenu::from_model(Some(menu));
+    popover.set_parent(column_view);
+    popover.set_has_arrow(false);
+    let gesture_click = GestureClick::new();
+    gesture_click.set_button(3); // Secondary (right) mouse button.
+    column_view.add_controller(gesture_click.clone());
+    gesture_click.connect_pressed(clone!(@weak popover, @weak selection_model => move |gesture, _n_press, x, y| {
+        if selection_model.selection().is_empty() {
+            return;
+        }
+        gesture.set_state(Claimed);
+        popover.set_pointing_to(Some(&Rectangle::new(x as i32, y as i32, 1, 1)));
+        popover.popup();
+    }));
+}
+
+/// Adds a parameterless action to the group that runs `activate`.
+pub fn add_action(actions: &SimpleActionGroup, name: &str, activate: impl Fn() + 'static) {
+    let action = SimpleAction::new(name, None);
+    action.connect_activate(move |_, _| activate());
+    actions.add_action(&action);
+}
+
+/// Returns the encoded text of all rows that are currently selected in the results view.
+fn selected_rows(selection_model: &MultiSelection) -> Vec<String> {
+    (0..selection_model.n_items())
+        .filter(|&i| selection_model.is_selected(i))
+        .filter_map(|i| selection_model.item(i))
+        .filter_map(|item| item.downcast_ref::<StringObject>().map(|string_obj| string_obj.string().to_string()))
+        .collect()
+}
+
+/// Lets the user choose an application to open a log with.
+///
+/// Logs stored inside an archive are first extracted to a temporary file.
+fn open_with(window: &ApplicationWindow, path: &Path) {
+    let path = if is_virtual_path(path) {
+        match extract_to_temp(path) {
+            Ok(extracted) => extracted,
+            Err(err) => {
+                show_error_dialog(window, &format!("Failed to extract {}: {}", path.display(), err));
+                return;
+            }
+        }
+    } else {
+        path.to_path_buf()
+    };
+    let file = File::for_path(&path);
+    let dialog = AppChooserDialog::new(Some(window), DialogFlags::MODAL, &file);
+    dialog.connect_response(clone!(@weak window => move |dialog, response| {
+        if response == ResponseOk
+            && let Some(app_info) = dialog.app_info()
+            && let Err(err) = app_info.launch(from_ref(&file), None::<&AppLaunchContext>) {
+            show_error_dialog(&window, &format!("Failed to open file: {}", err));
+        }
+        dialog.close();
+    }));
+    dialog.show();
+}
+
+/// Shows the given files in the file manager, selected in their folders.
+///
+/// The file manager is asked through the `org.freedesktop.FileManager1` D-Bus interface. If
+/// no file manager implements it, the parent folder of the first file is opened instead.
+/// Logs inside an archive are shown as the archive itself.
+fn show_in_folder(window: &ApplicationWindow, paths: &[PathBuf]) {
+    let paths: Vec<PathBuf> = paths.iter().map(|path| physical_path(path)).collect();
+    let Some(first) = paths.first().cloned() else {
+        return;
+    };
+    let open_parent = clone!(@weak window => move || {
+        let parent = first.parent().unwrap_or(&first);
+        if let Err(err) = Command::new("xdg-open").arg(parent).spawn() {
+            show_error_dialog(&window, &format!("Failed to open folder: {}", err));
+        }
+    });
+    let Ok(connection) = bus_get_sync(BusType::Session, Cancellable::NONE) else {
+        open_parent();
+        return;
+    };
+    let uris: Vec<String> = paths.iter().map(|path| File::for_path(path).uri().to_string()).collect();
+    connection.call(
+        Some("org.freedesktop.FileManager1"),
+        "/org/freedesktop/FileManager1",
+        "org.freedesktop.FileManager1",
+        "ShowItems",
+        Some(&(uris, "").to_variant()),
+        None,
+        DBusCallFlags::NONE,
+        -1,
+        Cancellable::NONE,
+        move |reply| {
+            if reply.is_err() {
+                open_parent();
+            }
+        },
+    );
+}
+
+/// Analyzes the given logs again on a background thread and updates their results.
+///
+/// Marks set by a retention policy are kept.
+pub fn rescan_paths(paths: Vec<PathBuf>, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    if paths.is_empty() {
+        return;
+    }
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let rescanned: Vec<DRResult> = paths.iter().map(|path| analyze_file(path)).collect();
+        let _ = done_tx.send(rescanned);
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok(rescanned) = done_rx.try_recv() else {
+            return Continue;
+        };
+        if let Ok(mut state) = app_state.lock() {
+            let (mut removed, mut changed) = (Vec::new(), Vec::new());
+            for result in rescanned {
+                if let Some(existing) = state.results.iter_mut().find(|existing| existing.path == result.path) {
+                    let marked = existing.marked;
+                    removed.push(replace(existing, DRResult { marked, ..result }));
+                    changed.push(existing.clone());
+                }
+            }
+            update_rows(&list_store, &removed, &changed);
+        }
+        Break
+    }));
+}
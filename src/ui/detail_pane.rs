@@ -0,0 +1,240 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc::channel;
+use std::thread::spawn;
+use std::time::Duration;
+
+use glib::{clone, markup_escape_text, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{Box, Grid, Label, MultiSelection, Notebook, Orientation::Vertical, ScrolledWindow, StringObject, TextBuffer, TextTag, TextView, WrapMode};
+use gtk4::pango::EllipsizeMode::Middle;
+use libadwaita::prelude::{BoxExt, Cast, GridExt, ListModelExt, SelectionModelExt, TextBufferExt, TextViewExt, WidgetExt};
+
+use crate::dr_analyzer::log_parser::{parse_metadata, parse_tracks, read_log_text, LogMetadata, TrackEntry};
+use crate::data::tiers::{color_to_hex, tier_color};
+use crate::utils::constants::get_dr_regex;
+
+/// The color of tracks the meter could not measure (`DRERR`).
+const ERR_COLOR: (u8, u8, u8) = (128, 128, 128);
+
+/// Everything the detail pane shows about a log, read on a background thread.
+struct LogDetails {
+    path: PathBuf,
+    content: Option<String>,
+    metadata: LogMetadata,
+    tracks: Vec<TrackEntry>,
+}
+
+/// Creates the detail pane that shows the selected log inside the application window.
+///
+/// The "Tracks" tab shows the album metadata and the per-track table of the log, with the DR
+/// cells colored like the DR column. The "Log" tab shows the raw log text with the official DR
+/// line and the track lines highlighted. The pane follows the selection of the results view;
+/// it stays empty unless exactly one row is selected. Logs are read on a background thread.
+pub fn create_detail_pane(selection_model: &MultiSelection) -> Notebook {
+    let notebook = Notebook::new();
+
+    // --- Tracks ---
+    let tracks_box = Box::new(Vertical, 10);
+    tracks_box.set_margin_top(10);
+    tracks_box.set_margin_bottom(10);
+    tracks_box.set_margin_start(10);
+    tracks_box.set_margin_end(10);
+    let metadata_grid = Grid::new();
+    metadata_grid.set_row_spacing(5);
+    metadata_grid.set_column_spacing(10);
+    let tracks_grid = Grid::new();
+    tracks_grid.set_row_spacing(5);
+    tracks_grid.set_column_spacing(15);
+    tracks_box.append(&metadata_grid);
+    tracks_box.append(&tracks_grid);
+    let tracks_scrolled = ScrolledWindow::new();
+    tracks_scrolled.set_child(Some(&tracks_box));
+    notebook.append_page(&tracks_scrolled, Some(&Label::new(Some("Tracks"))));
+
+    // --- Raw log ---
+    let buffer = TextBuffer::new(None);
+    buffer.tag_table().add(&TextTag::builder().name("official").weight(700).build());
+    let text_view = TextView::with_buffer(&buffer);
+    text_view.set_editable(false);
+    text_view.set_monospace(true);
+    text_view.set_wrap_mode(WrapMode::None);
+    text_view.set_left_margin(10);
+    text_view.set_top_margin(10);
+    let log_scrolled = ScrolledWindow::new();
+    log_scrolled.set_child(Some(&text_view));
+    notebook.append_page(&log_scrolled, Some(&Label::new(Some("Log"))));
+
+    show_placeholder(&metadata_grid, &tracks_grid, &buffer, "Select a single log to see its details.");
+
+    // Only the most recently requested log is shown, even if an older one finishes loading later.
+    let shown_path: Rc<RefCell<Option<PathBuf>>> = Rc::new(RefCell::new(None));
+    let update = Rc::new(clone!(@weak metadata_grid, @weak tracks_grid, @weak buffer, @strong shown_path => move |selection_model: &MultiSelection| {
+        let selected: Vec<(PathBuf, String)> = (0..selection_model.n_items())
+            .filter(|&i| selection_model.is_selected(i))
+            .take(2)
+            .filter_map(|i| selection_model.item(i))
+            .filter_map(|item| item.downcast_ref::<StringObject>().map(|string_obj| {
+                let text = string_obj.string();
+                let mut fields = text.split('\t');
+                (fields.nth(1).map(PathBuf::from).unwrap_or_default(), fields.next().unwrap_or("PENDING").to_string())
+            }))
+            .collect();
+        let [(path, dr_text)] = selected.as_slice() else {
+            if shown_path.borrow_mut().take().is_some() {
+                show_placeholder(&metadata_grid, &tracks_grid, &buffer, "Select a single log to see its details.");
+            }
+            return;
+        };
+        if shown_path.borrow().as_ref() == Some(path) {
+            return;
+        }
+        *shown_path.borrow_mut() = Some(path.clone());
+        show_placeholder(&metadata_grid, &tracks_grid, &buffer, "Loading…");
+
+        let (done_tx, done_rx) = channel();
+        let log_path = path.clone();
+        spawn(move || {
+            let content = read_log_text(&log_path);
+            let metadata = content.as_deref().map(parse_metadata).unwrap_or_default();
+            let tracks = content.as_deref().map(parse_tracks).unwrap_or_default();
+            let _ = done_tx.send(LogDetails { path: log_path, content, metadata, tracks });
+        });
+        let dr_text = dr_text.clone();
+        timeout_add_local(Duration::from_millis(100), clone!(@weak metadata_grid, @weak tracks_grid, @weak buffer, @strong shown_path => @default-return Break, move || {
+            let Ok(details) = done_rx.try_recv() else {
+                return Continue;
+            };
+            if shown_path.borrow().as_ref() == Some(&details.path) {
+                show_details(&metadata_grid, &tracks_grid, &buffer, &details, &dr_text);
+            }
+            Break
+        }));
+    }));
+    selection_model.connect_selection_changed(clone!(@strong update => move |selection_model, _, _| update(selection_model)));
+    selection_model.connect_items_changed(clone!(@strong update => move |selection_model, _, _, _| update(selection_model)));
+
+    notebook
+}
+
+/// Clears the pane and shows `message` instead of a log.
+fn show_placeholder(metadata_grid: &Grid, tracks_grid: &Grid, buffer: &TextBuffer, message: &str) {
+    clear_grid(metadata_grid);
+    clear_grid(tracks_grid);
+    let label = Label::new(Some(message));
+    label.add_css_class("dim-label");
+    metadata_grid.attach(&label, 0, 0, 1, 1);
+    buffer.set_text("");
+}
+
+/// Removes all children of a grid.
+fn clear_grid(grid: &Grid) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+}
+
+/// Fills the pane with the metadata, the track table and the highlighted text of a log.
+fn show_details(metadata_grid: &Grid, tracks_grid: &Grid, buffer: &TextBuffer, details: &LogDetails, dr_text: &str) {
+    clear_grid(metadata_grid);
+    clear_grid(tracks_grid);
+    let Some(content) = &details.content else {
+        show_placeholder(metadata_grid, tracks_grid, buffer, &format!("Could not read {}.", details.path.display()));
+        return;
+    };
+
+    // Album metadata, with the official DR colored like the DR column.
+    let official = match dr_text.parse::<u8>() {
+        Ok(dr) => dr_markup(Some(dr)),
+        Err(_) => markup_escape_text(dr_text).to_string(),
+    };
+    let unknown = || "Unknown".to_string();
+    let rows = [
+        ("Artist", markup_escape_text(&details.metadata.artist.clone().unwrap_or_else(unknown)).to_string()),
+        ("Album", markup_escape_text(&details.metadata.album.clone().unwrap_or_else(unknown)).to_string()),
+        ("Year", markup_escape_text(&details.metadata.year.clone().unwrap_or_else(unknown)).to_string()),
+        ("Official DR", official),
+        ("Path", markup_escape_text(&details.path.display().to_string()).to_string()),
+    ];
+    for (row, (key, value)) in rows.iter().enumerate() {
+        let key_label = Label::new(Some(key));
+        key_label.set_xalign(0.0);
+        key_label.add_css_class("dim-label");
+        let value_label = Label::new(None);
+        value_label.set_markup(value);
+        value_label.set_xalign(0.0);
+        value_label.set_selectable(true);
+        value_label.set_ellipsize(Middle);
+        value_label.set_hexpand(true);
+        metadata_grid.attach(&key_label, 0, row as i32, 1, 1);
+        metadata_grid.attach(&value_label, 1, row as i32, 1, 1);
+    }
+
+    // The per-track table.
+    if details.tracks.is_empty() {
+        let label = Label::new(Some("This log has no per-track table."));
+        label.add_css_class("dim-label");
+        tracks_grid.attach(&label, 0, 0, 1, 1);
+    } else {
+        for (column, title) in ["DR", "Peak", "RMS", "Duration", "Track"].iter().enumerate() {
+            let header = Label::new(Some(title));
+            header.set_xalign(0.0);
+            header.add_css_class("heading");
+            tracks_grid.attach(&header, column as i32, 0, 1, 1);
+        }
+        for (row, track) in details.tracks.iter().enumerate() {
+            let dr_label = Label::new(None);
+            dr_label.set_markup(&dr_markup(track.dr));
+            dr_label.set_xalign(0.0);
+            tracks_grid.attach(&dr_label, 0, row as i32 + 1, 1, 1);
+            for (column, text) in [&track.peak, &track.rms, &track.duration, &track.title].iter().enumerate() {
+                let label = Label::new(Some(text));
+                label.set_xalign(if column < 3 { 1.0 } else { 0.0 });
+                tracks_grid.attach(&label, column as i32 + 1, row as i32 + 1, 1, 1);
+            }
+        }
+    }
+
+    // The raw log, with the official DR line and the track lines highlighted.
+    buffer.set_text(content);
+    for (line, text) in content.lines().enumerate() {
+        if get_dr_regex().is_match(text) {
+            highlight_line(buffer, line, "official");
+        }
+    }
+    for track in &details.tracks {
+        let color = track.dr.map(tier_color).unwrap_or(ERR_COLOR);
+        let name = format!("dr-{}", color_to_hex(color));
+        if buffer.tag_table().lookup(&name).is_none() {
+            buffer.tag_table().add(&TextTag::builder()
+                .name(name.as_str())
+                .paragraph_background(color_to_hex(color))
+                .foreground("black")
+                .build());
+        }
+        highlight_line(buffer, track.line, &name);
+    }
+}
+
+/// Applies the tag `name` to a whole line of the buffer.
+fn highlight_line(buffer: &TextBuffer, line: usize, name: &str) {
+    if let Some(start) = buffer.iter_at_line(line as i32) {
+        let mut end = start;
+        end.forward_to_line_end();
+        buffer.apply_tag_by_name(name, &start, &end);
+    }
+}
+
+/// Returns Pango markup that shows a track's DR ("DR12", or "ERR") on the color of its tier.
+pub fn dr_markup(dr: Option<u8>) -> String {
+    match dr {
+        Some(dr) => colored_markup(&format!("DR{}", dr), tier_color(dr)),
+        None => colored_markup("ERR", ERR_COLOR),
+    }
+}
+
+/// Returns Pango markup that shows `text` on a background of the given color.
+fn colored_markup(text: &str, color: (u8, u8, u8)) -> String {
+    format!("<span background=\"{}\" foreground=\"black\"> {} </span>", color_to_hex(color), markup_escape_text(text))
+}
@@ -1,18 +1,100 @@
-use gtk4::{ButtonsType::Ok, DialogFlags, MessageDialog, MessageType::Error};
-use libadwaita::ApplicationWindow;
-use libadwaita::prelude::{DialogExt, GtkWindowExt, WidgetExt};
+use std::cell::RefCell;
+use std::sync::Arc;
 
-// Opens file in system's default application, showing error dialog on failure
+use glib::{MainContext, SendWeakRef, WeakRef};
+use libadwaita::{ApplicationWindow, MessageDialog, Toast, ToastOverlay};
+use libadwaita::prelude::{AdwApplicationWindowExt, CastNone, GtkWindowExt, MessageDialogExt, ObjectExt};
+
+use crate::data::app_state::ProblemHandler;
+
+thread_local! {
+    /// The toast overlay of the active window, which shows application-wide problems.
+    static TOAST_OVERLAY: RefCell<WeakRef<ToastOverlay>> = RefCell::new(WeakRef::new());
+
+    /// The toasts currently reporting problems, with their overlay and the number of problems
+    /// each of them covers.
+    static PROBLEM_TOASTS: RefCell<Vec<(WeakRef<ToastOverlay>, Toast, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Shows an error message in a modal dialog that is closed with "OK".
 pub fn show_error_dialog(window: &ApplicationWindow, message: &str) {
-    let dialog = MessageDialog::new(
-        Some(window),
-        DialogFlags::MODAL,
-        Error,
-        Ok,
-        message
-    );
-
-    // Auto-close dialog on response to prevent memory leaks
-    dialog.connect_response(|dialog, _| dialog.close());
-    dialog.show();
+    let dialog = MessageDialog::new(Some(window), Some("Error"), Some(message));
+    dialog.add_response("ok", "OK");
+    dialog.set_default_response(Some("ok"));
+    dialog.set_close_response("ok");
+    dialog.present();
+}
+
+/// Registers the overlay of the active window that `report_problem` shows its toasts in.
+pub fn install_toast_overlay(overlay: &ToastOverlay) {
+    TOAST_OVERLAY.with(|current| current.borrow().set(Some(overlay)));
+}
+
+/// Reports a non-fatal problem of the application, such as a setting that could not be saved,
+/// in a toast of the active window.
+///
+/// This can be called from any thread; the toast is shown on the main thread. Without a main
+/// window, the problem is printed instead. Problems of a workspace are reported through its
+/// own handler instead (see `problem_handler`).
+pub fn report_problem(message: String) {
+    MainContext::default().invoke(move || {
+        match TOAST_OVERLAY.with(|overlay| overlay.borrow().upgrade()) {
+            Some(overlay) => show_problem(&overlay, &message),
+            None => eprintln!("{}", message),
+        }
+    });
+}
+
+/// Returns a handler that reports problems in a toast of `overlay`, such as the overlay of a
+/// workspace, so that problems of a scan in a background tab stay with that tab.
+///
+/// The handler can be called from any thread; the toast is shown on the main thread. Once the
+/// overlay is gone, problems are printed instead.
+pub fn problem_handler(overlay: &ToastOverlay) -> ProblemHandler {
+    let overlay: SendWeakRef<ToastOverlay> = overlay.downgrade().into();
+    Arc::new(move |message| {
+        let overlay = overlay.clone();
+        MainContext::default().invoke(move || {
+            match overlay.upgrade() {
+                Some(overlay) => show_problem(&overlay, &message),
+                None => eprintln!("{}", message),
+            }
+        });
+    })
+}
+
+/// Shows a problem in a toast of `overlay`.
+///
+/// Problems that arrive while a toast is still shown in the same overlay are counted in that
+/// toast instead of queuing one toast each.
+fn show_problem(overlay: &ToastOverlay, message: &str) {
+    PROBLEM_TOASTS.with_borrow_mut(|toasts| {
+        toasts.retain(|(shown_in, _, _)| shown_in.upgrade().is_some());
+        if let Some((_, toast, count)) = toasts.iter_mut().find(|(shown_in, _, _)| shown_in.upgrade().as_ref() == Some(overlay)) {
+            *count += 1;
+            toast.set_title(&glib::markup_escape_text(&format!("{} problems, latest: {}", count, message)));
+            return;
+        }
+        let toast = Toast::new(&glib::markup_escape_text(message));
+        toast.connect_dismissed(|toast| {
+            PROBLEM_TOASTS.with_borrow_mut(|toasts| toasts.retain(|(_, shown, _)| shown != toast));
+        });
+        overlay.add_toast(toast.clone());
+        toasts.push((overlay.downgrade(), toast, 1));
+    });
+}
+
+/// Reports a problem in a toast of `window` with a "Details" button that shows `details` in a dialog.
+pub fn report_problem_with_details(window: &ApplicationWindow, message: &str, details: String) {
+    let Some(overlay) = window.content().and_downcast::<ToastOverlay>() else {
+        show_error_dialog(window, &format!("{}\n{}", message, details));
+        return;
+    };
+    let toast = Toast::new(&glib::markup_escape_text(message));
+    toast.set_button_label(Some("Details"));
+    toast.set_timeout(10);
+    toast.connect_button_clicked(glib::clone!(@weak window => move |_| {
+        show_error_dialog(&window, &details);
+    }));
+    overlay.add_toast(toast);
 }
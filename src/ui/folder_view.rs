@@ -0,0 +1,402 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use gio::{ListModel, ListStore, Menu, SimpleActionGroup};
+use glib::{clone, timeout_add_local_once, Object, SourceId, WeakRef};
+use gtk4::{ColumnView, ColumnViewColumn, Label, MultiSelection, ScrolledWindow, SignalListItemFactory, StringObject, TreeExpander, TreeListModel, TreeListRow};
+use gtk4::pango::EllipsizeMode::End;
+use gtk4::PolicyType::{Automatic, Never};
+use libadwaita::prelude::{Cast, CastNone, ListItemExt, ListModelExt, ObjectExt, SelectionModelExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::data::folder_tree::{build_folder_tree, FolderNode};
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::file_ops::{remove_files, try_open_file};
+use crate::ui::column_view::{row_field, select_on_right_click};
+use crate::ui::context_menu::{add_action, rescan_paths, setup_row_menu};
+use crate::ui::detail_pane::dr_markup;
+use crate::ui::workspace::workspace_window;
+
+/// How long the folder view waits for the results to settle before it is rebuilt.
+const REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// The indexes of the tab-separated fields of a row, see `node_text`.
+const PATH_FIELD: usize = 0;
+const NAME_FIELD: usize = 1;
+const KIND_FIELD: usize = 2;
+const LOGS_FIELD: usize = 3;
+const MIN_FIELD: usize = 4;
+const MEAN_FIELD: usize = 5;
+const MAX_FIELD: usize = 6;
+
+/// Creates the folder view of the results, an alternative to the flat results list.
+///
+/// The view mirrors the folder tree beneath the scan roots (see `build_folder_tree`) in a
+/// `TreeListModel`. Every folder shows the number of logs beneath it and the min, average
+/// and max DR of them, and every log its own DR value. Folders can be expanded and
+/// collapsed, and the context menu acts on all logs beneath the selected folders: Open,
+/// Rescan, Remove from List and Delete.
+///
+/// The view is rebuilt from `app_state` whenever the results change while it is shown; the
+/// search bar only filters the flat list. Only the rows that changed are replaced, and
+/// expanded folders stay expanded and selected rows selected. Returns the scrolled view with
+/// its `ColumnView` and selection.
+pub fn create_folder_view(list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) -> (ScrolledWindow, ColumnView, MultiSelection) {
+
+    // The rows are encoded as "path\tname\tkind\tlogs\tmin\tmean\tmax", like the results list.
+    // The children of every folder are kept by path until the folder is expanded, and the
+    // stores of the expanded folders are kept by path to update them.
+    let root_store = ListStore::new::<StringObject>();
+    let children: Rc<RefCell<HashMap<PathBuf, Vec<String>>>> = Rc::new(RefCell::new(HashMap::new()));
+    let child_stores: Rc<RefCell<HashMap<PathBuf, WeakRef<ListStore>>>> = Rc::new(RefCell::new(HashMap::new()));
+    let tree_model = TreeListModel::new(root_store.clone(), false, false, clone!(@strong children, @strong child_stores => move |item: &Object| {
+        let text = item.downcast_ref::<StringObject>()?.string();
+        if row_field(&text, KIND_FIELD) != "folder" {
+            return None;
+        }
+        let path = PathBuf::from(row_field(&text, PATH_FIELD));
+        let store = ListStore::new::<StringObject>();
+        for row in children.borrow().get(&path)? {
+            store.append(&StringObject::new(row));
+        }
+        child_stores.borrow_mut().insert(path, store.downgrade());
+        Some(store.upcast::<ListModel>())
+    }));
+    let selection_model = MultiSelection::new(Some(tree_model.clone()));
+    let column_view = ColumnView::new(Some(selection_model.clone()));
+    column_view.add_css_class("data-table");
+    add_folder_column(&column_view);
+    add_stats_column(&column_view, "Logs", LOGS_FIELD, false);
+    add_stats_column(&column_view, "Min DR", MIN_FIELD, true);
+    add_stats_column(&column_view, "Avg DR", MEAN_FIELD, false);
+    add_stats_column(&column_view, "Max DR", MAX_FIELD, true);
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_hexpand(true);
+    scrolled.set_vexpand(true);
+    scrolled.set_policy(Never, Automatic);
+    scrolled.set_child(Some(&column_view));
+
+    // Rebuild the tree from the results. Unchanged rows are kept, so the view keeps its scroll
+    // position; rows that were replaced are expanded and selected again like before.
+    let refresh = Rc::new(clone!(@weak tree_model, @weak selection_model, @weak root_store, @strong children, @strong child_stores, @strong app_state => move || {
+        let Ok(state) = app_state.lock() else {
+            return;
+        };
+        let tree = build_folder_tree(&state.roots, &state.results);
+        drop(state);
+        let expanded: HashSet<PathBuf> = (0..tree_model.n_items())
+            .filter_map(|i| tree_model.row(i))
+            .filter(|row| row.is_expanded())
+            .filter_map(|row| row_path(&row))
+            .collect();
+        let selected: HashSet<PathBuf> = selected_row_paths(&selection_model).into_iter().collect();
+
+        let mut new_children = HashMap::new();
+        collect_children(&tree, &mut new_children);
+        *children.borrow_mut() = new_children;
+        let stores: Vec<(PathBuf, ListStore)> = {
+            let mut child_stores = child_stores.borrow_mut();
+            child_stores.retain(|_, store| store.upgrade().is_some());
+            child_stores.iter().filter_map(|(path, store)| Some((path.clone(), store.upgrade()?))).collect()
+        };
+        for (path, store) in stores {
+            update_store(&store, children.borrow().get(&path).map_or(&[], Vec::as_slice));
+        }
+        update_store(&root_store, &tree.iter().map(node_text).collect::<Vec<String>>());
+
+        let mut shown = Vec::new();
+        collect_shown_rows(&tree, &expanded, &selected, &mut shown);
+        for (i, shown_row) in (0..).zip(shown) {
+            let Some(row) = tree_model.row(i) else {
+                break;
+            };
+            if row_path(&row).as_ref() != Some(&shown_row.path) {
+                break;
+            }
+            if shown_row.expand && !row.is_expanded() {
+                row.set_expanded(true);
+            }
+            if shown_row.select {
+                selection_model.select_item(i, false);
+            }
+        }
+    }));
+
+    // Follow the results while the view is shown, once a burst of changes is over.
+    let stale = Rc::new(Cell::new(true));
+    let pending_refresh: Rc<RefCell<Option<SourceId>>> = Rc::new(RefCell::new(None));
+    list_store.connect_items_changed(clone!(@weak scrolled, @strong refresh, @strong stale, @strong pending_refresh => move |_, _, _, _| {
+        if !scrolled.is_mapped() {
+            stale.set(true);
+            return;
+        }
+        if pending_refresh.borrow().is_some() {
+            return;
+        }
+        let source_id = timeout_add_local_once(REFRESH_DELAY, clone!(@strong refresh, @strong pending_refresh => move || {
+            pending_refresh.borrow_mut().take();
+            refresh();
+        }));
+        *pending_refresh.borrow_mut() = Some(source_id);
+    }));
+    scrolled.connect_map(clone!(@strong refresh, @strong stale => move |_| {
+        if stale.replace(false) {
+            refresh();
+        }
+    }));
+
+    setup_folder_menu(&column_view, &selection_model, list_store, app_state);
+    (scrolled, column_view, selection_model)
+}
+
+/// Sets up the context menu of the folder view, which acts on all logs beneath the selected rows.
+///
+/// Like the results view, right-clicking a row that is not selected selects it first (see
+/// `setup_row_menu`).
+fn setup_folder_menu(column_view: &ColumnView, selection_model: &MultiSelection, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let actions = SimpleActionGroup::new();
+    add_action(&actions, "open", clone!(@weak column_view, @weak selection_model => move || {
+        let Some(window) = workspace_window(&column_view) else {
+            return;
+        };
+        for path in selected_row_paths(&selection_model) {
+            try_open_file(&window, &path);
+        }
+    }));
+    add_action(&actions, "rescan", clone!(@weak selection_model, @weak list_store, @strong app_state => move || {
+        rescan_paths(logs_beneath(&selected_row_paths(&selection_model), &app_state), &list_store, &app_state);
+    }));
+    add_action(&actions, "remove-from-list", clone!(@weak selection_model, @weak list_store, @strong app_state => move || {
+        let logs: HashSet<PathBuf> = logs_beneath(&selected_row_paths(&selection_model), &app_state).into_iter().collect();
+        if let Ok(mut state) = app_state.lock() {
+            let removed: Vec<DRResult> = state.results.extract_if(.., |result| logs.contains(&result.path)).collect();
+            update_rows(&list_store, &removed, &[]);
+        }
+    }));
+    add_action(&actions, "delete", clone!(@weak column_view, @weak selection_model, @weak list_store, @strong app_state => move || {
+        let logs = logs_beneath(&selected_row_paths(&selection_model), &app_state);
+        if let Some(window) = workspace_window(&column_view)
+            && !logs.is_empty() {
+            remove_files(&window, logs, &list_store, &app_state);
+        }
+    }));
+
+    let menu = Menu::new();
+    menu.append(Some("Open"), Some("row.open"));
+    let list_section = Menu::new();
+    list_section.append(Some("Rescan"), Some("row.rescan"));
+    list_section.append(Some("Remove from List"), Some("row.remove-from-list"));
+    list_section.append(Some("Delete"), Some("row.delete"));
+    menu.append_section(None, &list_section);
+    setup_row_menu(column_view, selection_model, &actions, &menu);
+}
+
+/// Adds the "Folder" column, which shows the name of a folder or log with an expander.
+fn add_folder_column(column_view: &ColumnView) {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(move |_, list_item| {
+        let label = Label::new(None);
+        label.set_xalign(0.0);
+        label.set_ellipsize(End);
+        let expander = TreeExpander::new();
+        expander.set_child(Some(&label));
+        list_item.set_child(Some(&expander));
+        select_on_right_click(list_item);
+    });
+    factory.connect_bind(move |_, list_item| {
+        let row = list_item.item().and_downcast::<TreeListRow>().unwrap();
+        let expander = list_item.child().and_downcast::<TreeExpander>().unwrap();
+        let label = expander.child().and_downcast::<Label>().unwrap();
+        let text = row.item().and_downcast::<StringObject>().map(|item| item.string()).unwrap_or_default();
+        label.set_text(row_field(&text, NAME_FIELD));
+        expander.set_list_row(Some(&row));
+    });
+    let column = ColumnViewColumn::new(Some("Folder"), Some(factory));
+    column.set_resizable(true);
+    column.set_expand(true);
+    column_view.append_column(&column);
+}
+
+/// Adds a column showing the field at `index` of the rows, with DR values on their tier color if `colored`.
+fn add_stats_column(column_view: &ColumnView, title: &str, index: usize, colored: bool) {
+    let factory = SignalListItemFactory::new();
+    factory.connect_setup(move |_, list_item| {
+        let label = Label::new(None);
+        label.set_xalign(1.0);
+        label.set_margin_start(5);
+        label.set_margin_end(5);
+        list_item.set_child(Some(&label));
+        select_on_right_click(list_item);
+    });
+    factory.connect_bind(move |_, list_item| {
+        let row = list_item.item().and_downcast::<TreeListRow>().unwrap();
+        let label = list_item.child().and_downcast::<Label>().unwrap();
+        let text = row.item().and_downcast::<StringObject>().map(|item| item.string()).unwrap_or_default();
+        let field = row_field(&text, index);
+        match field.parse::<u8>() {
+            Ok(dr) if colored => label.set_markup(&dr_markup(Some(dr))),
+            _ if field.is_empty() => label.set_text("—"),
+            _ => label.set_text(field),
+        }
+    });
+    let column = ColumnViewColumn::new(Some(title), Some(factory));
+    column.set_resizable(true);
+    column.set_fixed_width(90);
+    column_view.append_column(&column);
+}
+
+/// Formats a node as a row of the folder view: "path\tname\tkind\tlogs\tmin\tmean\tmax".
+///
+/// The DR fields are empty if there is no DR value beneath the node.
+fn node_text(node: &FolderNode) -> String {
+    let stats = node.stats;
+    format!("{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        node.path.display(),
+        node.name,
+        if node.is_log { "log" } else { "folder" },
+        stats.logs,
+        stats.min.map_or(String::new(), |dr| dr.to_string()),
+        stats.mean.map_or(String::new(), |mean| format!("{:.1}", mean)),
+        stats.max.map_or(String::new(), |dr| dr.to_string())
+    )
+}
+
+/// Stores the rows of the children of every folder in the tree by the path of the folder.
+fn collect_children(nodes: &[FolderNode], children: &mut HashMap<PathBuf, Vec<String>>) {
+    for node in nodes.iter().filter(|node| !node.is_log) {
+        children.insert(node.path.clone(), node.children.iter().map(node_text).collect());
+        collect_children(&node.children, children);
+    }
+}
+
+/// A row as it is shown in the folder view after a refresh.
+#[derive(Debug, PartialEq)]
+struct ShownRow {
+    path: PathBuf,
+    /// Whether the row is a folder that was expanded before the refresh.
+    expand: bool,
+    /// Whether the row was selected before the refresh.
+    select: bool,
+}
+
+/// Lists the rows shown for `nodes` in the order of the view, with the children of the
+/// folders in `expanded`, and which of them to expand and select again.
+fn collect_shown_rows(nodes: &[FolderNode], expanded: &HashSet<PathBuf>, selected: &HashSet<PathBuf>, rows: &mut Vec<ShownRow>) {
+    for node in nodes {
+        let expand = !node.is_log && expanded.contains(&node.path);
+        rows.push(ShownRow { path: node.path.clone(), expand, select: selected.contains(&node.path) });
+        if expand {
+            collect_shown_rows(&node.children, expanded, selected, rows);
+        }
+    }
+}
+
+/// Replaces the rows of `store` with `rows`, keeping the rows at the start and the end that
+/// did not change.
+fn update_store(store: &ListStore, rows: &[String]) {
+    let current: Vec<String> = (0..store.n_items())
+        .filter_map(|i| store.item(i).and_downcast::<StringObject>())
+        .map(|item| item.string().to_string())
+        .collect();
+    let (start, current_end, end) = changed_range(&current, rows);
+    if start == current_end && start == end {
+        return;
+    }
+    let added: Vec<StringObject> = rows[start..end].iter().map(|row| StringObject::new(row)).collect();
+    store.splice(start as u32, (current_end - start) as u32, &added);
+}
+
+/// Returns the range of `current` that has to be replaced by a range of `rows` to turn it into
+/// `rows`, as the start of both ranges and their ends.
+fn changed_range(current: &[String], rows: &[String]) -> (usize, usize, usize) {
+    let start = current.iter().zip(rows).take_while(|(a, b)| a == b).count();
+    let unchanged_end = current[start..].iter().rev()
+        .zip(rows[start..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (start, current.len() - unchanged_end, rows.len() - unchanged_end)
+}
+
+/// Returns the path of the folder or log shown in a row.
+fn row_path(row: &TreeListRow) -> Option<PathBuf> {
+    let item = row.item().and_downcast::<StringObject>()?;
+    Some(PathBuf::from(row_field(&item.string(), PATH_FIELD)))
+}
+
+/// Returns the paths of all folders and logs that are selected in the folder view.
+pub fn selected_row_paths(selection_model: &MultiSelection) -> Vec<PathBuf> {
+    (0..selection_model.n_items())
+        .filter(|&i| selection_model.is_selected(i))
+        .filter_map(|i| selection_model.item(i).and_downcast::<TreeListRow>())
+        .filter_map(|row| row_path(&row))
+        .collect()
+}
+
+/// Returns the paths of all results at or beneath the given folders and logs.
+pub fn logs_beneath(paths: &[PathBuf], app_state: &Arc<Mutex<AppState>>) -> Vec<PathBuf> {
+    let Ok(state) = app_state.lock() else {
+        return Vec::new();
+    };
+    state.results.iter()
+        .filter(|result| paths.iter().any(|path| result.path.starts_with(path)))
+        .map(|result| result.path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str) -> DRResult {
+        let path = PathBuf::from(path);
+        DRResult {
+            dr_value: Some(12),
+            filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path,
+            scanned: true,
+            is_new: false,
+            marked: false,
+            invalid: false,
+        }
+    }
+
+    fn shown_paths(rows: &[ShownRow]) -> Vec<&str> {
+        rows.iter().map(|row| row.path.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn expanded_folders_stay_expanded_after_a_refresh() {
+        let roots = [PathBuf::from("/music")];
+        let before = build_folder_tree(&roots, &[result("/music/A/dr.txt"), result("/music/B/dr.txt")]);
+        let expanded = HashSet::from([PathBuf::from("/music"), PathBuf::from("/music/A")]);
+        let selected = HashSet::from([PathBuf::from("/music/A/dr.txt")]);
+        let mut rows = Vec::new();
+        collect_shown_rows(&before, &expanded, &selected, &mut rows);
+        assert_eq!(shown_paths(&rows), ["/music", "/music/A", "/music/A/dr.txt", "/music/B"]);
+
+        // A new log in the expanded folder shows up, and the folders stay expanded.
+        let after = build_folder_tree(&roots, &[result("/music/A/dr.txt"), result("/music/A/CD2/dr.txt"), result("/music/B/dr.txt")]);
+        let mut rows = Vec::new();
+        collect_shown_rows(&after, &expanded, &selected, &mut rows);
+        assert_eq!(shown_paths(&rows), ["/music", "/music/A", "/music/A/CD2", "/music/A/dr.txt", "/music/B"]);
+        let expand: Vec<&str> = rows.iter().filter(|row| row.expand).map(|row| row.path.to_str().unwrap()).collect();
+        assert_eq!(expand, ["/music", "/music/A"]);
+        let select: Vec<&str> = rows.iter().filter(|row| row.select).map(|row| row.path.to_str().unwrap()).collect();
+        assert_eq!(select, ["/music/A/dr.txt"]);
+    }
+
+    #[test]
+    fn only_changed_rows_are_replaced() {
+        let rows = |names: &[&str]| names.iter().map(|name| name.to_string()).collect::<Vec<String>>();
+        assert_eq!(changed_range(&rows(&["a", "b", "c"]), &rows(&["a", "b", "c"])), (3, 3, 3));
+        assert_eq!(changed_range(&rows(&["a", "b", "c"]), &rows(&["a", "x", "y", "c"])), (1, 2, 3));
+        assert_eq!(changed_range(&rows(&["a", "b"]), &rows(&["a", "b", "c"])), (2, 2, 3));
+        assert_eq!(changed_range(&rows(&["a", "b", "c"]), &rows(&["c"])), (0, 2, 0));
+        assert_eq!(changed_range(&rows(&["a", "a"]), &rows(&["a"])), (1, 2, 1));
+    }
+}
@@ -1,112 +1,228 @@
-use std::sync::{Arc, Mutex};
+use std::cell::{Cell, RefCell};
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
-use gio::ListStore;
+use gio::{File, ListStore, SimpleAction, ThemedIcon};
 use glib::clone;
-use gtk4::{Button, FileChooserAction::SelectFolder, FileChooserDialog, Label, ProgressBar};
+use gtk4::{Button, FileChooserAction::SelectFolder, FileChooserDialog, ListBox, ProgressBar, ToggleButton, Widget};
 use gtk4::ResponseType::{Accept, Cancel};
-use libadwaita::ApplicationWindow;
-use libadwaita::prelude::{ButtonExt, DialogExt, FileChooserExt, FileExt, GtkWindowExt, ListModelExt, WidgetExt};
+use libadwaita::{Application, TabPage};
+use libadwaita::prelude::{ActionExt, ActionMapExt, ButtonExt, CastNone, ObjectExt, ToggleButtonExt, ToVariant, DialogExt, FileChooserExt, FileExt, GtkWindowExt, ListModelExt, WidgetExt};
 
-use crate::data::app_state::{AppState, DRResult};
-use crate::dr_analyzer::analyzer::{scan_directory, update_ui};
-use crate::file_manager::file_ops::find_log_files;
-use crate::ui::dialogs::show_error_dialog;
+use crate::data::app_state::AppState;
+use crate::data::settings::{current_settings, update_settings};
+use crate::dr_analyzer::analyzer::{scan_directory, ScanControl};
+use crate::file_manager::watcher::LogWatcher;
+use crate::ui::scan_roots::{add_scan_roots, refresh_roots_list};
+use crate::ui::workspace::workspace_window;
 
-/// Connects the primary header bar buttons to their respective actions and manages their state.
+/// The widgets of a workspace that drive its scans, connected by `setup_button_actions`.
+pub struct WorkspaceControls {
+    /// The workspace itself, used to find the window it is shown in.
+    pub workspace: Widget,
+    pub open_button: Button,
+    pub scan_button: Button,
+    pub pause_button: ToggleButton,
+    pub clear_button: Button,
+    /// The list of scan roots in the popover of the roots button.
+    pub roots_list: ListBox,
+    pub progress_bar: ProgressBar,
+}
+
+/// Connects the primary toolbar buttons of a workspace to their actions and manages their state.
 ///
 /// This function orchestrates the main user workflow:
-/// 1.  **Open Button**: Triggers a `FileChooserDialog` to select a directory. Upon selection,
-///     it finds all `.log` and `.txt` files, populates the `list_store` with initial
-///     (unscanned) results, and enables the "Scan" and "Clear" buttons.
-/// 2.  **Scan Button**: Initiates the asynchronous analysis of the files in the selected directory.
-///     It disables itself and the "Clear" button during the scan and shows the progress bar.
+/// 1.  **Open Button**: Triggers a `FileChooserDialog` to select one or more directories. Each
+///     selection is added as a scan root; its `.log` and `.txt` files are merged into the
+///     `list_store` as initial (unscanned) results, and the "Scan" and "Clear" buttons are enabled.
+/// 2.  **Scan Button**: Initiates the asynchronous analysis of the files in all scan roots and
+///     shows the progress bar. While scanning, it turns into a "Stop" button that cancels the
+///     scan, and the **Pause** toggle next to it suspends and resumes the worker threads.
 /// 3.  **Clear Button**: Removes all items from the `list_store` and clears the internal
-///     application state, resetting the UI to its initial state.
+///     application state, including the scan roots, resetting the UI to its initial state.
+///     A running scan is cancelled and its remaining results are discarded.
 ///
 /// The sensitivity of the "Scan" and "Clear" buttons is automatically managed based on whether
 /// the `list_store` contains any items.
-pub fn setup_button_actions(window: &ApplicationWindow, open_button: &Button, 
-                       scan_button: &Button, clear_button: &Button, 
-                       selected_path: &Arc<Mutex<Option<PathBuf>>>, list_store: &ListStore, 
-                       app_state: &Arc<Mutex<AppState>>, progress_bar: &ProgressBar, 
-                       file_count_label: &Label) {
-    
+pub fn setup_button_actions(controls: &WorkspaceControls, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let WorkspaceControls { workspace, open_button, scan_button, pause_button, clear_button, roots_list, progress_bar } = controls;
+
     // Automatically update button sensitivity when the list store changes.
-    list_store.connect_items_changed(clone!(@weak scan_button, @weak clear_button, @weak file_count_label => move |list_store, _, _, _| {
+    list_store.connect_items_changed(clone!(@weak scan_button, @weak clear_button => move |list_store, _, _, _| {
         let has_items = list_store.n_items() > 0;
         clear_button.set_sensitive(has_items);
         scan_button.set_sensitive(has_items);
-        file_count_label.set_text(&format!("Files: {}", list_store.n_items()));
     }));
 
     // The "Clear" button resets the application state.
-    clear_button.connect_clicked(clone!(@strong list_store, @strong app_state, @strong scan_button, @strong clear_button, @strong file_count_label => move |_| {
+    clear_button.connect_clicked(clone!(@strong list_store, @strong app_state, @strong roots_list, @strong scan_button, @strong pause_button, @strong clear_button => move |_| {
         list_store.remove_all();
         if let Ok(mut state) = app_state.lock() {
             state.results.clear();
+            state.roots.clear();
+
+            // Stop a running scan; its remaining results are discarded.
+            if let Some(control) = state.scan_control.take() {
+                control.cancel();
+            }
         }
+        set_scan_running(&scan_button, &pause_button, false);
+        refresh_roots_list(&roots_list, &list_store, &app_state);
         scan_button.set_sensitive(false);
         clear_button.set_sensitive(false);
-        file_count_label.set_text("Files: 0");
     }));
 
     // The "Open" button shows a directory selection dialog.
-    open_button.connect_clicked(clone!(@strong window, @strong scan_button, @strong clear_button, @strong selected_path, @strong list_store, @strong app_state, @strong file_count_label => move |_| {
+    open_button.connect_clicked(clone!(@weak workspace, @strong roots_list, @strong list_store, @strong app_state => move |_| {
+        let Some(window) = workspace_window(&workspace) else {
+            return;
+        };
         let dialog = FileChooserDialog::new(
-            Some("Select Directory"),
+            Some("Select Directories"),
             Some(&window),
             SelectFolder,
             &[("Cancel", Cancel), ("Open", Accept)]
         );
-        dialog.connect_response(clone!(@strong window, @strong scan_button, @strong clear_button, @strong selected_path, @strong list_store, @strong app_state, @strong file_count_label => move |dialog, response| {
+        dialog.set_select_multiple(true);
+        dialog.connect_response(clone!(@strong window, @strong roots_list, @strong list_store, @strong app_state => move |dialog, response| {
             if response == Accept {
-                if let Some(path) = dialog.file().and_then(|f| f.path()) {
-
-                    // Store the selected path and find all log files within it.
-                    *selected_path.lock().unwrap() = Some(path.clone());
-                    let mut files = Vec::new();
-                    if let Err(err) = find_log_files(&path, &mut files) {
-                        show_error_dialog(&window, &format!("Error reading directory: {}", err));
-                        return;
-                    }
-
-                    // Create an initial list of results with a "pending" state.
-                    let initial_results: Vec<DRResult> = files.iter().map(|path: &PathBuf| DRResult {
-                        filename: path.file_name().unwrap().to_string_lossy().into_owned(),
-                        path: path.clone(),
-                        dr_value: None,
-                        scanned: false, // Mark as unscanned initially.
-                    }).collect();
-
-                    // Update the application state and the UI.
-                    if let Ok(mut state) = app_state.lock() {
-                        state.results = initial_results;
-                        update_ui(&list_store, &state.results);
-                        let has_items = !state.results.is_empty();
-                        scan_button.set_sensitive(has_items);
-                        clear_button.set_sensitive(has_items);
-                        file_count_label.set_text(&format!("Files: {}", list_store.n_items()));
-                    }
-                }
+                let files = dialog.files();
+                let paths: Vec<PathBuf> = (0..files.n_items())
+                    .filter_map(|i| files.item(i).and_downcast::<File>())
+                    .filter_map(|file| file.path())
+                    .collect();
+
+                // Merge the logs of the selected directories into the existing list.
+                add_scan_roots(&window, paths, &roots_list, &list_store, &app_state);
             }
             dialog.close();
         }));
         dialog.show();
     }));
 
-    // The "Scan" button initiates the DR value analysis.
-    scan_button.connect_clicked(clone!(@strong app_state, @strong progress_bar, @strong list_store, @strong selected_path, @strong clear_button => move |button| {
-        if let Some(path) = selected_path.lock().unwrap().clone() {
-
-            // Disable buttons and show progress bar during scan.
-            button.set_sensitive(false);
-            clear_button.set_sensitive(false);
-            progress_bar.set_visible(true);
-            progress_bar.set_fraction(0.0);
-            
-            // Start the asynchronous scan.
-            scan_directory(path, app_state.clone(), progress_bar.clone(), list_store.clone(), button.clone(), clear_button.clone());
+    // The "Scan" button initiates the DR value analysis, or stops the scan that is running.
+    scan_button.connect_clicked(clone!(@strong app_state, @strong progress_bar, @strong list_store, @strong pause_button => move |button| {
+        let Ok(mut state) = app_state.lock() else {
+            return;
+        };
+
+        // While scanning, the button acts as a "Stop" button.
+        if let Some(control) = &state.scan_control {
+            control.cancel();
+            button.set_sensitive(false); // Re-enabled once the workers have stopped.
+            return;
+        }
+        let roots = state.roots.clone();
+        if roots.is_empty() {
+            return;
+        }
+        let control = Arc::new(ScanControl::default());
+        state.scan_control = Some(control.clone());
+        drop(state);
+
+        // Turn the button into a "Stop" button and show the progress bar during the scan.
+        set_scan_running(button, &pause_button, true);
+        progress_bar.set_visible(true);
+        progress_bar.set_fraction(0.0);
+
+        // Start the asynchronous scan.
+        scan_directory(roots, control, app_state.clone(), progress_bar.clone(), list_store.clone(), clone!(@weak button, @weak pause_button, @strong app_state => move || {
+
+            // Only restore the buttons if no other scan has been started in the meantime.
+            if app_state.lock().map(|state| state.scan_control.is_none()).unwrap_or(true) {
+                set_scan_running(&button, &pause_button, false);
+            }
+        }));
+    }));
+
+    // The pause toggle suspends the walker and analyzer threads until it is released.
+    pause_button.connect_toggled(clone!(@strong app_state => move |button| {
+        if let Ok(state) = app_state.lock()
+            && let Some(control) = &state.scan_control {
+            control.set_paused(button.is_active());
+        }
+        button.set_tooltip_text(Some(if button.is_active() { "Resume Scan" } else { "Pause Scan" }));
+    }));
+}
+
+/// Switches the scan button between its "Scan" and "Stop" appearance.
+///
+/// The pause toggle is only shown while a scan is running and is released whenever
+/// the state changes.
+fn set_scan_running(scan_button: &Button, pause_button: &ToggleButton, running: bool) {
+    if running {
+        scan_button.set_icon_name("media-playback-stop-symbolic");
+        scan_button.set_tooltip_text(Some("Stop Scan"));
+        scan_button.remove_css_class("suggested-action");
+        scan_button.add_css_class("destructive-action");
+    } else {
+        scan_button.set_icon_name("view-refresh-symbolic");
+        scan_button.set_tooltip_text(Some("Scan Files"));
+        scan_button.remove_css_class("destructive-action");
+        scan_button.add_css_class("suggested-action");
+        scan_button.set_sensitive(true);
+    }
+    pause_button.set_active(false);
+    pause_button.set_visible(running);
+}
+
+/// Adds the "app.read-only" action, which switches read-only mode for the whole application.
+///
+/// The read-only toggle in the header bar of every window is bound to this action, so all
+/// windows show the same state. The file operations refuse to change anything while it is
+/// active (see `current_settings`), so keyboard shortcuts and policies are covered too.
+pub fn setup_read_only_action(app: &Application) {
+    let action = SimpleAction::new_stateful("read-only", None, &current_settings().read_only.to_variant());
+    action.connect_change_state(|action, state| {
+        if let Some(read_only) = state.and_then(|state| state.get::<bool>()) {
+            update_settings(|settings| settings.read_only = read_only);
+            action.set_state(&read_only.to_variant());
+        }
+    });
+    app.add_action(&action);
+}
+
+/// Shows read-only mode in a workspace, now and whenever it is switched.
+///
+/// While read-only mode is active, the buttons of destructive actions are insensitive and
+/// the tab of the workspace shows a lock icon. The connection ends when `workspace` is destroyed.
+pub fn setup_read_only_indicator(workspace: &Widget, page: &TabPage, destructive_buttons: &[Button]) {
+    let destructive_buttons = destructive_buttons.to_vec();
+    let show = clone!(@weak page => move |read_only: bool| {
+        for destructive_button in &destructive_buttons {
+            destructive_button.set_sensitive(!read_only);
+        }
+        page.set_indicator_icon(read_only.then(|| ThemedIcon::new("changes-prevent-symbolic")).as_ref());
+        page.set_indicator_tooltip(if read_only { "Read-only" } else { "" });
+    });
+    show(current_settings().read_only);
+    let Some(action) = gio::Application::default().and_then(|app| app.lookup_action("read-only")) else {
+        return;
+    };
+    let handler = action.connect_state_notify(move |action| {
+        show(action.state().and_then(|state| state.get::<bool>()).unwrap_or(false));
+    });
+    let handler = Cell::new(Some(handler));
+    workspace.connect_destroy(move |_| {
+        if let Some(handler) = handler.take() {
+            action.disconnect(handler);
+        }
+    });
+}
+
+/// Connects the watch mode toggle in the toolbar of a workspace.
+///
+/// While the toggle is active, a `LogWatcher` follows all scan roots and adds new or changed
+/// logs to the results as soon as they are fully written. Deactivating the toggle stops it.
+pub fn setup_watch_button(watch_button: &ToggleButton, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let watcher: Rc<RefCell<Option<Rc<LogWatcher>>>> = Rc::new(RefCell::new(None));
+    watch_button.connect_toggled(clone!(@strong list_store, @strong app_state => move |button| {
+        if let Some(previous) = watcher.borrow_mut().take() {
+            previous.stop();
+        }
+        if button.is_active() {
+            *watcher.borrow_mut() = Some(LogWatcher::start(&list_store, &app_state));
         }
     }));
 }
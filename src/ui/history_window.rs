@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, mpsc::channel};
+use std::thread::spawn;
+use std::time::Duration;
+
+use gio::ListStore;
+use glib::{clone, format_size, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{Box, Button, Label, ListBox, Orientation::{Horizontal, Vertical}, ScrolledWindow, SelectionMode, Window};
+use gtk4::pango::EllipsizeMode::Middle;
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{BoxExt, ButtonExt, GtkWindowExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::data::settings::current_settings;
+use crate::dr_analyzer::analyzer::{analyze_file, update_rows};
+use crate::file_manager::file_ops::{collect_root_files, root_contains};
+use crate::file_manager::journal::{journal_path, read_journal, restore_from_trash, JournalEntry, Operation};
+use crate::file_manager::safety::READ_ONLY_MESSAGE;
+use crate::ui::dialogs::show_error_dialog;
+
+/// The number of journal entries shown in the History window, newest first.
+const HISTORY_LIMIT: usize = 1000;
+
+/// Shows the History window listing the most recent entries of the journal.
+///
+/// Every trash, delete, move, rename and restore performed by the application is listed with
+/// its time, path, size and outcome. Items that were moved to the trash can be restored to
+/// their original location; restored logs inside a scan root, including the logs of restored
+/// folders and archives, are read on a background thread and added back to the results.
+pub fn show_history_window(window: &ApplicationWindow, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let history = Window::new();
+    history.set_title(Some("History"));
+    history.set_transient_for(Some(window));
+    history.set_default_size(900, 500);
+
+    let vbox = Box::new(Vertical, 10);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+    let location_label = Label::new(Some(&format!("Journal: {}", journal_path().display())));
+    location_label.set_xalign(0.0);
+    location_label.set_selectable(true);
+    location_label.add_css_class("dim-label");
+    vbox.append(&location_label);
+
+    let entries_list = ListBox::new();
+    entries_list.set_selection_mode(SelectionMode::None);
+    let entries = read_journal();
+    if entries.is_empty() {
+        entries_list.set_placeholder(Some(&Label::new(Some("No file operations recorded yet."))));
+    }
+    for entry in entries.into_iter().rev().take(HISTORY_LIMIT) {
+        entries_list.append(&create_entry_row(window, entry, list_store, app_state));
+    }
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&entries_list));
+    vbox.append(&scrolled);
+
+    history.set_child(Some(&vbox));
+    history.present();
+}
+
+/// Creates the row for a single journal entry, with a restore button for trashed items.
+fn create_entry_row(window: &ApplicationWindow, entry: JournalEntry, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) -> Box {
+    let hbox = Box::new(Horizontal, 10);
+    hbox.set_margin_top(5);
+    hbox.set_margin_bottom(5);
+    hbox.set_margin_start(5);
+    hbox.set_margin_end(5);
+
+    let time_label = Label::new(Some(entry.timestamp.split('.').next().unwrap_or("")));
+    time_label.add_css_class("dim-label");
+    let operation_label = Label::new(Some(entry.operation.as_str()));
+    operation_label.set_width_chars(8);
+    operation_label.set_xalign(0.0);
+    operation_label.add_css_class("heading");
+    let path_text = match &entry.destination {
+        Some(destination) => format!("{} → {}", entry.path.display(), destination.display()),
+        None => entry.path.display().to_string(),
+    };
+    let path_label = Label::new(Some(&path_text));
+    path_label.set_xalign(0.0);
+    path_label.set_hexpand(true);
+    path_label.set_ellipsize(Middle);
+    path_label.set_tooltip_text(Some(&path_text));
+    let size_label = Label::new(Some(&format_size(entry.size)));
+    let outcome_label = Label::new(Some(if entry.error.is_some() { "Failed" } else { "OK" }));
+    if let Some(error) = &entry.error {
+        outcome_label.add_css_class("error");
+        outcome_label.set_tooltip_text(Some(error));
+    }
+    hbox.append(&time_label);
+    hbox.append(&operation_label);
+    hbox.append(&path_label);
+    hbox.append(&size_label);
+    hbox.append(&outcome_label);
+
+    if entry.operation == Operation::Trash && entry.error.is_none() {
+        let restore_button = Button::with_label("Restore");
+        restore_button.connect_clicked(clone!(@weak window, @strong list_store, @strong app_state => move |button| {
+            if current_settings().read_only {
+                show_error_dialog(&window, READ_ONLY_MESSAGE);
+                return;
+            }
+            if let Err(err) = restore_from_trash(&entry.path) {
+                show_error_dialog(&window, &format!("Could not restore {}: {}", entry.path.display(), err));
+                return;
+            }
+            button.set_label("Restored");
+            button.set_sensitive(false);
+
+            // Add the restored logs back to the results if they belong to one of the scan roots.
+            let Ok((roots, report_problem)) = app_state.lock().map(|state| (state.roots.clone(), state.report_problem.clone())) else {
+                return;
+            };
+            if !roots.iter().any(|root| root_contains(root, &entry.path)) {
+                return;
+            }
+            let path = entry.path.clone();
+            let (done_tx, done_rx) = channel();
+            spawn(move || {
+                let results: Vec<DRResult> = match collect_root_files(&path, &report_problem) {
+                    Ok(files) => files.iter().map(|file| analyze_file(file)).collect(),
+                    Err(err) => {
+                        report_problem(format!("Failed to read {}: {}", path.display(), err));
+                        Vec::new()
+                    }
+                };
+                done_tx.send(results).ok();
+            });
+            timeout_add_local(Duration::from_millis(100), clone!(@strong list_store, @strong app_state => @default-return Break, move || {
+                let Ok(results) = done_rx.try_recv() else {
+                    return Continue;
+                };
+                if let Ok(mut state) = app_state.lock() {
+                    let known: HashSet<PathBuf> = state.results.iter().map(|result| result.path.clone()).collect();
+                    let added: Vec<DRResult> = results.into_iter().filter(|result| !known.contains(&result.path)).collect();
+                    update_rows(&list_store, &[], &added);
+                    state.results.extend(added);
+                }
+                Break
+            }));
+        }));
+        hbox.append(&restore_button);
+    }
+    hbox
+}
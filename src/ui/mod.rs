@@ -1,5 +1,20 @@
+pub mod album_dialog;
 pub mod app_window;
 pub mod column_view;
+pub mod compare_window;
+pub mod context_menu;
+pub mod detail_pane;
 pub mod dialogs;
+pub mod folder_view;
 pub mod header_bar;
+pub mod history_window;
+pub mod move_dialog;
+pub mod policy_dialog;
+pub mod rename_dialog;
+pub mod scan_roots;
+pub mod search_bar;
 pub mod settings_dialog;
+pub mod shortcuts_window;
+pub mod statistics_window;
+pub mod tier_editor;
+pub mod workspace;
@@ -0,0 +1,248 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, mpsc::channel};
+use std::thread::spawn;
+use std::time::Duration;
+
+use gio::ListStore;
+use glib::{clone, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{Box, Button, DropDown, Entry, FileChooserAction::SelectFolder, FileChooserDialog, Label, Orientation::{Horizontal, Vertical}, ScrolledWindow, TextView};
+use gtk4::ResponseType::{Accept, Cancel};
+use gtk4::WrapMode::WordChar;
+use libadwaita::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use libadwaita::prelude::{BoxExt, ButtonExt, DialogExt, EditableExt, FileChooserExt, FileExt, GtkWindowExt, MessageDialogExt, TextBufferExt, TextViewExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::data::settings::current_settings;
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::album_cleanup::album_folders;
+use crate::file_manager::organizer::{move_path, plan_moves, rebase_path, ConflictPolicy, MoveTarget, PlannedMove};
+use crate::file_manager::safety::{confine_to_roots, READ_ONLY_MESSAGE};
+use crate::file_manager::templates::{template_values, TemplateValues, TEMPLATE_TOKENS};
+use crate::ui::dialogs::{report_problem_with_details, show_error_dialog};
+
+/// The template suggested when the dialog is opened.
+const DEFAULT_MOVE_TEMPLATE: &str = "{tier}/{artist} - {album}";
+
+/// Shows the "Move to…" dialog for the selected logs at `paths`.
+///
+/// The user picks a destination folder, a template such as `{tier}/{artist} - {album}`,
+/// whether to move only the log or its whole album folder, and how to resolve conflicts.
+/// The selected logs are read on a background thread first, to find their album metadata.
+/// A preview lists every planned move and is updated as the options change. The moves are
+/// performed on a background thread (copying across filesystems if necessary), after which
+/// the paths in `AppState` are updated without a rescan. Failures are reported in a toast.
+pub fn show_move_dialog(window: &ApplicationWindow, paths: Vec<PathBuf>,
+                        list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let paths: HashSet<PathBuf> = paths.into_iter().collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    if current_settings().read_only {
+        show_error_dialog(window, READ_ONLY_MESSAGE);
+        return;
+    }
+    let (results, roots, known_albums) = match app_state.lock() {
+        Ok(state) => (
+            state.results.iter().filter(|result| paths.contains(&result.path)).cloned().collect::<Vec<DRResult>>(),
+            state.roots.clone(),
+            album_folders(state.results.iter().map(|result| result.path.as_path())),
+        ),
+        Err(_) => return,
+    };
+
+    // Gather the template values of every selected log once; this reads the logs, so it
+    // happens on a background thread and the dialog is shown when it is done.
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let items: Vec<(DRResult, TemplateValues)> = results.into_iter()
+            .map(|result| {
+                let values = template_values(&result, &roots);
+                (result, values)
+            })
+            .collect();
+        done_tx.send((items, roots)).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok((items, roots)) = done_rx.try_recv() else {
+            return Continue;
+        };
+        present_move_dialog(&window, items, roots, known_albums.clone(), &list_store, &app_state);
+        Break
+    }));
+}
+
+/// Creates the "Move to…" dialog for the selected results and their template values.
+///
+/// The scan roots and the known album folders decide which album folders may be moved.
+fn present_move_dialog(window: &ApplicationWindow, items: Vec<(DRResult, TemplateValues)>, roots: Vec<PathBuf>,
+                       known_albums: BTreeSet<PathBuf>, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+
+    // Create the modal dialog; the options and the preview are shown below its body.
+    let dialog = MessageDialog::new(Some(window), Some("Move To…"), Some(&format!("Move {} log(s) into a folder built from the template.", items.len())));
+    dialog.set_default_size(700, 500);
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("move", "Move");
+    dialog.set_response_appearance("move", ResponseAppearance::Suggested);
+    dialog.set_response_enabled("move", false);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let vbox = Box::new(Vertical, 10);
+
+    // --- Destination folder ---
+    let destination: Rc<RefCell<Option<PathBuf>>> = Rc::new(RefCell::new(None));
+    let destination_box = Box::new(Horizontal, 10);
+    let destination_label = Label::new(Some("No destination selected"));
+    destination_label.set_hexpand(true);
+    destination_label.set_xalign(0.0);
+    let destination_button = Button::with_label("Choose Folder…");
+    destination_box.append(&destination_label);
+    destination_box.append(&destination_button);
+    vbox.append(&destination_box);
+
+    // --- Template ---
+    let template_entry = Entry::new();
+    template_entry.set_text(DEFAULT_MOVE_TEMPLATE);
+    vbox.append(&template_entry);
+    let tokens_label = Label::new(Some(&format!("Available tokens: {}", TEMPLATE_TOKENS)));
+    tokens_label.set_xalign(0.0);
+    tokens_label.add_css_class("dim-label");
+    vbox.append(&tokens_label);
+
+    // --- Move target and conflict policy ---
+    let options_box = Box::new(Horizontal, 10);
+    let target_dropdown = DropDown::from_strings(&["Move log file only", "Move whole album folder"]);
+    let conflict_dropdown = DropDown::from_strings(&["Skip existing destinations", "Rename on conflict"]);
+    options_box.append(&target_dropdown);
+    options_box.append(&conflict_dropdown);
+    vbox.append(&options_box);
+
+    // --- Preview ---
+    let preview = TextView::new();
+    preview.set_editable(false);
+    preview.set_monospace(true);
+    preview.set_wrap_mode(WordChar);
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_min_content_height(250);
+    scrolled.set_child(Some(&preview));
+    vbox.append(&scrolled);
+    dialog.set_extra_child(Some(&vbox));
+
+    // Recompute the plan and its preview whenever an option changes.
+    let plan: Rc<RefCell<Vec<PlannedMove>>> = Rc::new(RefCell::new(Vec::new()));
+    let refresh = Rc::new(clone!(@strong plan, @strong destination, @weak template_entry, @weak target_dropdown, @weak conflict_dropdown, @weak preview, @weak dialog => move || {
+        let Some(destination_root) = destination.borrow().clone() else {
+            preview.buffer().set_text("Choose a destination folder to preview the moves.");
+            return;
+        };
+        let target = if target_dropdown.selected() == 1 { MoveTarget::AlbumFolder } else { MoveTarget::LogFile };
+        let policy = if conflict_dropdown.selected() == 1 { ConflictPolicy::Rename } else { ConflictPolicy::Skip };
+        let new_plan = plan_moves(&items, &destination_root, &template_entry.text(), target, policy, &roots, &known_albums);
+        let text: Vec<String> = new_plan.iter().map(|planned| {
+            if let Some(reason) = &planned.refused {
+                return format!("[refused] {}\n    {}", planned.source.display(), reason);
+            }
+            let marker = if planned.skipped { "[skip]   " } else if planned.renamed { "[rename] " } else { "" };
+            format!("{}{}\n    → {}", marker, planned.source.display(), planned.destination.display())
+        }).collect();
+        preview.buffer().set_text(&text.join("\n"));
+        dialog.set_response_enabled("move", new_plan.iter().any(PlannedMove::is_performed));
+        *plan.borrow_mut() = new_plan;
+    }));
+    refresh();
+    template_entry.connect_changed(clone!(@strong refresh => move |_| refresh()));
+    target_dropdown.connect_selected_notify(clone!(@strong refresh => move |_| refresh()));
+    conflict_dropdown.connect_selected_notify(clone!(@strong refresh => move |_| refresh()));
+
+    // The destination button shows a folder selection dialog.
+    destination_button.connect_clicked(clone!(@weak dialog, @strong destination, @strong destination_label, @strong refresh => move |_| {
+        let chooser = FileChooserDialog::new(
+            Some("Select Destination"),
+            Some(&dialog),
+            SelectFolder,
+            &[("Cancel", Cancel), ("Select", Accept)]
+        );
+        chooser.connect_response(clone!(@strong destination, @strong destination_label, @strong refresh => move |chooser, response| {
+            if response == Accept
+                && let Some(path) = chooser.file().and_then(|f| f.path()) {
+                destination_label.set_text(&path.to_string_lossy());
+                *destination.borrow_mut() = Some(path);
+                refresh();
+            }
+            chooser.close();
+        }));
+        chooser.show();
+    }));
+
+    dialog.connect_response(None, clone!(@weak window, @strong plan, @strong list_store, @strong app_state => move |_, response| {
+        if response == "move" {
+            let moves: Vec<PlannedMove> = plan.borrow().iter().filter(|planned| planned.is_performed()).cloned().collect();
+            execute_moves(&window, moves, &list_store, &app_state);
+        }
+    }));
+    dialog.present();
+}
+
+/// Performs the planned moves on a background thread and updates the results afterwards.
+///
+/// Sources outside the scan roots (see `confine_to_roots`) are not moved, and nothing is
+/// moved in read-only mode.
+pub fn execute_moves(window: &ApplicationWindow, moves: Vec<PlannedMove>, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let Ok(roots) = app_state.lock().map(|state| state.roots.clone()) else {
+        return;
+    };
+    let read_only = current_settings().read_only;
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let outcomes: Vec<(PlannedMove, Result<(), String>)> = moves.into_iter()
+            .map(|planned| {
+                let outcome = if read_only {
+                    Err(READ_ONLY_MESSAGE.to_string())
+                } else {
+                    confine_to_roots(&planned.source, &roots)
+                        .and_then(|_| move_path(&planned.source, &planned.destination).map_err(|err| err.to_string()))
+                };
+                (planned, outcome)
+            })
+            .collect();
+        done_tx.send(outcomes).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok(outcomes) = done_rx.try_recv() else {
+            return Continue;
+        };
+        let mut failures = Vec::new();
+        if let Ok(mut state) = app_state.lock() {
+
+            // The results before their first move, by index; a result may be moved more than once.
+            let mut previous = BTreeMap::new();
+            for (planned, outcome) in outcomes {
+                if let Err(err) = outcome {
+                    failures.push(format!("{}: {}", planned.source.display(), err));
+                    continue;
+                }
+
+                // Point every result at or beneath the moved source to its new location.
+                for (index, result) in state.results.iter_mut().enumerate() {
+                    if let Some(new_path) = rebase_path(&result.path, &planned.source, &planned.destination) {
+                        previous.entry(index).or_insert_with(|| result.clone());
+                        result.filename = new_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                        result.path = new_path;
+                    }
+                }
+            }
+            let changed: Vec<DRResult> = previous.keys().map(|&index| state.results[index].clone()).collect();
+            update_rows(&list_store, &previous.into_values().collect::<Vec<_>>(), &changed);
+        }
+        if !failures.is_empty() {
+            report_problem_with_details(&window, &format!("Could not move {} item(s)", failures.len()), failures.join("\n"));
+        }
+        Break
+    }));
+}
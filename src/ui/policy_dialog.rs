@@ -0,0 +1,451 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use gio::ListStore;
+use glib::clone;
+use glib::Propagation::Proceed;
+use gtk4::{Align, Box, Button, DropDown, Entry, Label, ListBox, ListBoxRow, Orientation::{Horizontal, Vertical}, ScrolledWindow, SelectionMode, SpinButton, TextView, Window};
+use gtk4::WrapMode::WordChar;
+use libadwaita::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use libadwaita::prelude::{BoxExt, ButtonExt, CastNone, EditableExt, EntryExt, GtkWindowExt, ListBoxRowExt, MessageDialogExt, TextBufferExt, TextViewExt, WidgetExt};
+
+use crate::data::app_state::AppState;
+use crate::data::settings::current_settings;
+use crate::data::retention::{evaluate_policy, DrCondition, LogKind, RetentionAction, RetentionMatch, RetentionRule, Validity};
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::album_cleanup::{album_folder_target, album_folders};
+use crate::file_manager::archive::is_virtual_path;
+use crate::file_manager::file_ops::delete_paths;
+use crate::file_manager::organizer::PlannedMove;
+use crate::file_manager::safety::READ_ONLY_MESSAGE;
+use crate::file_manager::templates::physical_path;
+use crate::ui::album_dialog::remove_album_folders;
+use crate::ui::move_dialog::execute_moves;
+
+/// The widgets editing a single rule of the retention policy.
+struct RuleRow {
+    row: ListBoxRow,
+    dr_mode: DropDown,
+    dr_value: SpinButton,
+    validity: DropDown,
+    older_than: SpinButton,
+    user: Entry,
+    kind: DropDown,
+    action: DropDown,
+    destination: Entry,
+    remove_button: Button,
+}
+
+impl RuleRow {
+
+    /// Creates the widgets for a rule, initialized from `rule`.
+    fn new(rule: &RetentionRule) -> Self {
+        let vbox = Box::new(Vertical, 5);
+        vbox.set_margin_top(5);
+        vbox.set_margin_bottom(5);
+        vbox.set_margin_start(5);
+        vbox.set_margin_end(5);
+
+        // --- Conditions ---
+        let conditions = Box::new(Horizontal, 5);
+        let dr_mode = DropDown::from_strings(&["Any DR", "DR below", "DR at least"]);
+        let dr_value = SpinButton::with_range(1.0, 20.0, 1.0);
+        let (mode, value) = match rule.dr {
+            DrCondition::Any => (0, 8),
+            DrCondition::Below(value) => (1, value),
+            DrCondition::AtLeast(value) => (2, value),
+        };
+        dr_mode.set_selected(mode);
+        dr_value.set_value(value as f64);
+        let validity = DropDown::from_strings(&["Valid or invalid", "Valid only", "Invalid only"]);
+        validity.set_selected(match rule.validity {
+            Validity::Any => 0,
+            Validity::Valid => 1,
+            Validity::Invalid => 2,
+        });
+        let older_than = SpinButton::with_range(0.0, 3650.0, 1.0);
+        older_than.set_value(rule.older_than_days.unwrap_or(0) as f64);
+        older_than.set_tooltip_text(Some("Minimum age in days (0 matches any age)"));
+        let days_label = Label::new(Some("days old"));
+        let user = Entry::new();
+        user.set_placeholder_text(Some("Any user"));
+        user.set_text(rule.user.as_deref().unwrap_or(""));
+        let kind = DropDown::from_strings(&["Any kind", "Log files", "Archived logs"]);
+        kind.set_selected(match rule.kind {
+            LogKind::Any => 0,
+            LogKind::File => 1,
+            LogKind::Archived => 2,
+        });
+        conditions.append(&dr_mode);
+        conditions.append(&dr_value);
+        conditions.append(&validity);
+        conditions.append(&older_than);
+        conditions.append(&days_label);
+        conditions.append(&user);
+        conditions.append(&kind);
+        vbox.append(&conditions);
+
+        // --- Action ---
+        let action_box = Box::new(Horizontal, 5);
+        let action = DropDown::from_strings(&["Mark", "Move to trash", "Move album folder to trash", "Move to folder"]);
+        let destination = Entry::new();
+        destination.set_placeholder_text(Some("Destination folder"));
+        destination.set_hexpand(true);
+        match &rule.action {
+            RetentionAction::Mark => action.set_selected(0),
+            RetentionAction::Trash => action.set_selected(1),
+            RetentionAction::TrashAlbum => action.set_selected(2),
+            RetentionAction::Move(folder) => {
+                action.set_selected(3);
+                destination.set_text(&folder.to_string_lossy());
+            }
+        }
+        let remove_button = Button::from_icon_name("list-remove-symbolic");
+        remove_button.set_tooltip_text(Some("Remove Rule"));
+        action_box.append(&Label::new(Some("Then:")));
+        action_box.append(&action);
+        action_box.append(&destination);
+        action_box.append(&remove_button);
+        vbox.append(&action_box);
+
+        let row = ListBoxRow::new();
+        row.set_activatable(false);
+        row.set_child(Some(&vbox));
+        remove_button.connect_clicked(clone!(@weak row => move |_| {
+            if let Some(list) = row.parent().and_downcast::<ListBox>() {
+                list.remove(&row);
+            }
+        }));
+        RuleRow { row, dr_mode, dr_value, validity, older_than, user, kind, action, destination, remove_button }
+    }
+
+    /// Reads the rule back from the widgets.
+    ///
+    /// Fails if the rule moves logs but no destination folder was entered.
+    fn rule(&self) -> Result<RetentionRule, String> {
+        let dr_value = self.dr_value.value_as_int().clamp(0, u8::MAX as i32) as u8;
+        let action = match self.action.selected() {
+            1 => RetentionAction::Trash,
+            2 => RetentionAction::TrashAlbum,
+            3 => {
+                let folder = self.destination.text().trim().to_string();
+                if folder.is_empty() {
+                    return Err("A rule moves logs but has no destination folder.".to_string());
+                }
+                RetentionAction::Move(PathBuf::from(folder))
+            }
+            _ => RetentionAction::Mark,
+        };
+        let user = self.user.text().trim().to_string();
+        Ok(RetentionRule {
+            dr: match self.dr_mode.selected() {
+                1 => DrCondition::Below(dr_value),
+                2 => DrCondition::AtLeast(dr_value),
+                _ => DrCondition::Any,
+            },
+            validity: match self.validity.selected() {
+                1 => Validity::Valid,
+                2 => Validity::Invalid,
+                _ => Validity::Any,
+            },
+            older_than_days: Some(self.older_than.value_as_int() as u64).filter(|&days| days > 0),
+            user: Some(user).filter(|user| !user.is_empty()),
+            kind: match self.kind.selected() {
+                1 => LogKind::File,
+                2 => LogKind::Archived,
+                _ => LogKind::Any,
+            },
+            action,
+        })
+    }
+
+    /// Calls `changed` whenever one of the rule's widgets is edited or the rule is removed.
+    fn connect_changed(&self, changed: &Rc<dyn Fn()>) {
+        for dropdown in [&self.dr_mode, &self.validity, &self.kind, &self.action] {
+            dropdown.connect_selected_notify(clone!(@strong changed => move |_| changed()));
+        }
+        for spin_button in [&self.dr_value, &self.older_than] {
+            spin_button.connect_value_changed(clone!(@strong changed => move |_| changed()));
+        }
+        for entry in [&self.user, &self.destination] {
+            entry.connect_changed(clone!(@strong changed => move |_| changed()));
+        }
+        self.remove_button.connect_clicked(clone!(@strong changed => move |_| changed()));
+    }
+}
+
+/// Shows the retention policy editor.
+///
+/// A policy is a list of rules, each a set of conditions on DR value, validity, age, user
+/// folder and kind of log, together with an action: mark the logs, move them or their album
+/// folders to the trash, or move them into a folder. Logs are checked against the rules in order and the first
+/// matching rule applies; logs matching no rule are kept.
+///
+/// The policy can only be run after it was previewed against the current results, and the
+/// run has to be confirmed; any edit invalidates the preview. In read-only mode, only policies that merely mark logs
+/// can be run. Trashing goes through `delete_paths`, trashing album folders through
+/// `album_folder_target` and `remove_album_folders`, and moving through `execute_moves`, so
+/// the same checks apply and failures are reported the same way as for the manual actions.
+pub fn show_policy_dialog(window: &ApplicationWindow, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let rules = match app_state.lock() {
+        Ok(state) => state.retention_rules.clone(),
+        Err(_) => return,
+    };
+
+    // Create the modal window.
+    let dialog = Window::new();
+    dialog.set_title(Some("Retention Policy"));
+    dialog.set_transient_for(Some(window));
+    dialog.set_modal(true);
+    dialog.set_default_size(900, 600);
+
+    let vbox = Box::new(Vertical, 10);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+
+    // --- Rules ---
+    let rules_list = ListBox::new();
+    rules_list.set_selection_mode(SelectionMode::None);
+    rules_list.add_css_class("boxed-list");
+    let rules_scrolled = ScrolledWindow::new();
+    rules_scrolled.set_vexpand(true);
+    rules_scrolled.set_child(Some(&rules_list));
+    vbox.append(&rules_scrolled);
+    let add_button = Button::with_label("Add Rule");
+    add_button.set_halign(Align::Start);
+    vbox.append(&add_button);
+
+    // --- Preview ---
+    let preview = TextView::new();
+    preview.set_editable(false);
+    preview.set_monospace(true);
+    preview.set_wrap_mode(WordChar);
+    let preview_scrolled = ScrolledWindow::new();
+    preview_scrolled.set_vexpand(true);
+    preview_scrolled.set_child(Some(&preview));
+    vbox.append(&preview_scrolled);
+
+    // --- Buttons ---
+    let buttons = Box::new(Horizontal, 10);
+    buttons.set_halign(Align::End);
+    let cancel_button = Button::with_label("Cancel");
+    let preview_button = Button::with_label("Preview");
+    let run_button = Button::with_label("Run Policy");
+    run_button.add_css_class("destructive-action");
+    buttons.append(&cancel_button);
+    buttons.append(&preview_button);
+    buttons.append(&run_button);
+    vbox.append(&buttons);
+    dialog.set_child(Some(&vbox));
+
+    // Any edit discards the current preview, so the policy has to be previewed again.
+    let plan: Rc<RefCell<Option<Vec<RetentionMatch>>>> = Rc::new(RefCell::new(None));
+    let invalidate: Rc<dyn Fn()> = Rc::new(clone!(@strong plan, @weak preview, @weak run_button => move || {
+        *plan.borrow_mut() = None;
+        run_button.set_sensitive(false);
+        preview.buffer().set_text("Press \"Preview\" to see what the policy will do.");
+    }));
+    invalidate();
+
+    let rows: Rc<RefCell<Vec<RuleRow>>> = Rc::new(RefCell::new(Vec::new()));
+    let add_row = Rc::new(clone!(@strong rows, @strong invalidate, @weak rules_list => move |rule: &RetentionRule| {
+        let row = RuleRow::new(rule);
+        row.connect_changed(&invalidate);
+        rules_list.append(&row.row);
+        rows.borrow_mut().push(row);
+    }));
+    for rule in &rules {
+        add_row(rule);
+    }
+    add_button.connect_clicked(clone!(@strong add_row, @strong invalidate => move |_| {
+        add_row(&RetentionRule::default());
+        invalidate();
+    }));
+
+    // Reads the rules of all rows that were not removed from the list.
+    let read_rules = clone!(@strong rows => move || {
+        rows.borrow_mut().retain(|row| row.row.parent().is_some());
+        rows.borrow().iter().map(RuleRow::rule).collect::<Result<Vec<RetentionRule>, String>>()
+    });
+
+    // The rules are kept in the workspace whenever they are valid, even if the window is
+    // closed without running them.
+    let save_rules = Rc::new(clone!(@strong app_state => move || {
+        let rules = read_rules();
+        if let Ok(rules) = &rules
+            && let Ok(mut state) = app_state.lock() {
+            state.retention_rules = rules.clone();
+        }
+        rules
+    }));
+    dialog.connect_close_request(clone!(@strong save_rules => @default-return Proceed, move |_| {
+        let _ = save_rules();
+        Proceed
+    }));
+    cancel_button.connect_clicked(clone!(@weak dialog => move |_| dialog.close()));
+
+    preview_button.connect_clicked(clone!(@weak preview, @weak run_button, @strong plan, @strong invalidate, @strong save_rules, @strong app_state => move |_| {
+        invalidate();
+        let rules = match save_rules() {
+            Ok(rules) => rules,
+            Err(err) => {
+                preview.buffer().set_text(&err);
+                return;
+            }
+        };
+        let (matches, roots, known_albums) = match app_state.lock() {
+            Ok(state) => (
+                evaluate_policy(&rules, &state.results, &state.roots),
+                state.roots.clone(),
+                album_folders(state.results.iter().map(|result| result.path.as_path())),
+            ),
+            Err(_) => return,
+        };
+        let read_only = current_settings().read_only;
+
+        // In read-only mode, only policies that merely mark logs can be run.
+        let blocked = read_only && matches.iter().any(|matched| matched.action != RetentionAction::Mark);
+        let mut description = describe_plan(&matches, &roots, &known_albums);
+        if blocked {
+            description = format!("{}\n\n{}", READ_ONLY_MESSAGE, description);
+        }
+        preview.buffer().set_text(&description);
+        run_button.set_sensitive(!matches.is_empty() && !blocked);
+        *plan.borrow_mut() = Some(matches);
+    }));
+
+    // Running the previewed policy has to be confirmed once more.
+    run_button.connect_clicked(clone!(@weak window, @weak dialog, @strong plan, @strong list_store, @strong app_state => move |_| {
+        let Some(matches) = plan.borrow().clone() else {
+            return;
+        };
+        let confirmation = MessageDialog::new(Some(&dialog), Some("Run Retention Policy?"), Some(&summarize_plan(&matches)));
+        confirmation.add_response("cancel", "Cancel");
+        confirmation.add_response("run", "Run Policy");
+        confirmation.set_response_appearance("run", ResponseAppearance::Destructive);
+        confirmation.set_default_response(Some("cancel"));
+        confirmation.set_close_response("cancel");
+        confirmation.connect_response(None, clone!(@weak window, @weak dialog, @strong plan, @strong list_store, @strong app_state => move |_, response| {
+            if response != "run" {
+                return;
+            }
+            if let Some(matches) = plan.borrow_mut().take() {
+                run_policy(&window, matches, &list_store, &app_state);
+            }
+            dialog.close();
+        }));
+        confirmation.present();
+    }));
+    dialog.present();
+}
+
+/// Summarizes how many logs each action of an evaluated policy affects, for the confirmation.
+fn summarize_plan(matches: &[RetentionMatch]) -> String {
+    let count = |wanted: fn(&RetentionAction) -> bool| matches.iter().filter(|matched| wanted(&matched.action)).count();
+    [
+        (count(|action| *action == RetentionAction::Mark), "will be marked"),
+        (count(|action| *action == RetentionAction::Trash), "will be moved to the trash"),
+        (count(|action| *action == RetentionAction::TrashAlbum), "will have their album folders moved to the trash"),
+        (count(|action| matches!(action, RetentionAction::Move(_))), "will be moved"),
+    ].iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, description)| format!("{} log(s) {}.", count, description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+
+/// Describes every action of an evaluated policy for the preview.
+///
+/// Album folders that cannot be removed (see `album_folder_target`) are listed as refused.
+fn describe_plan(matches: &[RetentionMatch], roots: &[PathBuf], known_albums: &BTreeSet<PathBuf>) -> String {
+    if matches.is_empty() {
+        return "No logs match the policy; everything is kept.".to_string();
+    }
+    let mut lines = vec![format!("{} log(s) match the policy:", matches.len())];
+    for matched in matches {
+        let action = match &matched.action {
+            RetentionAction::Mark => "mark".to_string(),
+            RetentionAction::Trash if is_virtual_path(&matched.path) => {
+                format!("move archive {} to trash", physical_path(&matched.path).display())
+            }
+            RetentionAction::Trash => "move to trash".to_string(),
+            RetentionAction::TrashAlbum => match album_folder_target(&matched.path, roots, known_albums) {
+                Ok(folder) => format!("move album folder {} to trash", folder.display()),
+                Err(reason) => format!("refused: {}", reason),
+            },
+            RetentionAction::Move(folder) => format!("move to {}", folder.display()),
+        };
+        lines.push(format!("[rule {}, {}] {}", matched.rule + 1, action, matched.path.display()));
+    }
+    lines.join("\n")
+}
+
+/// Performs the actions of an evaluated policy.
+///
+/// Logs inside an archive are trashed or moved together with the archive. Album folders
+/// that cannot be removed are skipped, as shown in the preview.
+fn run_policy(window: &ApplicationWindow, matches: Vec<RetentionMatch>, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let (roots, known_albums) = match app_state.lock() {
+        Ok(state) => (state.roots.clone(), album_folders(state.results.iter().map(|result| result.path.as_path()))),
+        Err(_) => return,
+    };
+    let mut marked = HashSet::new();
+    let mut trashed = Vec::new();
+    let mut trashed_sources = HashSet::new();
+    let mut albums = BTreeSet::new();
+    let mut moves = Vec::new();
+    let mut sources = HashSet::new();
+    for matched in matches {
+        match matched.action {
+            RetentionAction::Mark => {
+                marked.insert(matched.path);
+            }
+            RetentionAction::Trash => {
+                let source = physical_path(&matched.path);
+                if trashed_sources.insert(source.clone()) {
+                    trashed.push(source);
+                }
+            }
+            RetentionAction::TrashAlbum => {
+                if let Ok(folder) = album_folder_target(&matched.path, &roots, &known_albums) {
+                    albums.insert(folder);
+                }
+            }
+            RetentionAction::Move(folder) => {
+
+                // Logs inside an archive are moved together with the archive.
+                let source = physical_path(&matched.path);
+                if sources.insert(source.clone()) {
+                    let destination = folder.join(source.file_name().unwrap_or_default());
+                    moves.push(PlannedMove { source, destination, renamed: false, skipped: false, refused: None });
+                }
+            }
+        }
+    }
+    let Ok(mut state) = app_state.lock() else {
+        return;
+    };
+    let mut changed = Vec::new();
+    for result in state.results.iter_mut().filter(|result| marked.contains(&result.path)) {
+        result.marked = true;
+        changed.push(result.clone());
+    }
+    update_rows(list_store, &[], &changed);
+    drop(state);
+    let delete_folders = current_settings().delete_folders;
+    if !trashed.is_empty() {
+        delete_paths(window, &trashed, false, delete_folders, list_store, app_state);
+    }
+    if !albums.is_empty() {
+        remove_album_folders(window, albums.into_iter().collect(), roots, false, list_store, app_state);
+    }
+    if !moves.is_empty() {
+        execute_moves(window, moves, list_store, app_state);
+    }
+}
@@ -0,0 +1,203 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::rename;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, mpsc::channel};
+use std::thread::spawn;
+use std::time::Duration;
+
+use gio::ListStore;
+use glib::{clone, timeout_add_local};
+use glib::ControlFlow::{Break, Continue};
+use gtk4::{Box, Entry, Label, Orientation::Vertical, ScrolledWindow, TextView};
+use gtk4::WrapMode::WordChar;
+use libadwaita::{ApplicationWindow, MessageDialog, ResponseAppearance};
+use libadwaita::prelude::{BoxExt, EditableExt, GtkWindowExt, MessageDialogExt, TextBufferExt, TextViewExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::data::settings::current_settings;
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::journal::{journaled, Operation};
+use crate::file_manager::organizer::rebase_path;
+use crate::file_manager::safety::{confine_to_roots, READ_ONLY_MESSAGE};
+use crate::file_manager::renamer::{plan_renames, PlannedRename};
+use crate::file_manager::templates::{template_values, TemplateValues, TEMPLATE_TOKENS};
+use crate::ui::dialogs::{report_problem_with_details, show_error_dialog};
+
+/// The template suggested when the dialog is opened.
+const DEFAULT_RENAME_TEMPLATE: &str = "{artist} - {album} [DR{dr}]";
+
+/// Shows the batch rename dialog for the album folders of the selected logs at `paths`.
+///
+/// The selected logs are read on a background thread first, to find their album metadata.
+/// The user enters a template such as `{artist} - {album} [DR{dr}]`. A preview lists the
+/// old and new name of every folder and is updated as the template changes; renames that
+/// would collide are refused and marked as such. After renaming, the paths in `AppState`
+/// are updated without a rescan. Failures are reported in a toast.
+pub fn show_rename_dialog(window: &ApplicationWindow, paths: Vec<PathBuf>,
+                          list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let paths: HashSet<PathBuf> = paths.into_iter().collect();
+    if paths.is_empty() {
+        return;
+    }
+
+    if current_settings().read_only {
+        show_error_dialog(window, READ_ONLY_MESSAGE);
+        return;
+    }
+    let (results, roots) = match app_state.lock() {
+        Ok(state) => (
+            state.results.iter().filter(|result| paths.contains(&result.path)).cloned().collect::<Vec<DRResult>>(),
+            state.roots.clone(),
+        ),
+        Err(_) => return,
+    };
+
+    // Gather the template values of every selected log once; this reads the logs, so it
+    // happens on a background thread and the dialog is shown when it is done.
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let items: Vec<(DRResult, TemplateValues)> = results.into_iter()
+            .map(|result| {
+                let values = template_values(&result, &roots);
+                (result, values)
+            })
+            .collect();
+        done_tx.send((items, roots)).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok((items, roots)) = done_rx.try_recv() else {
+            return Continue;
+        };
+        present_rename_dialog(&window, items, roots, &list_store, &app_state);
+        Break
+    }));
+}
+
+/// Creates the batch rename dialog for the selected results and their template values.
+fn present_rename_dialog(window: &ApplicationWindow, items: Vec<(DRResult, TemplateValues)>, roots: Vec<PathBuf>,
+                         list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+
+    // Create the modal dialog; the template and the preview are shown below its body.
+    let dialog = MessageDialog::new(Some(window), Some("Rename Album Folders?"), Some(&format!("Rename the album folders of {} log(s) after the template.", items.len())));
+    dialog.set_default_size(700, 500);
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("rename", "Rename");
+    dialog.set_response_appearance("rename", ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let vbox = Box::new(Vertical, 10);
+
+    // --- Template ---
+    let template_entry = Entry::new();
+    template_entry.set_text(DEFAULT_RENAME_TEMPLATE);
+    vbox.append(&template_entry);
+    let tokens_label = Label::new(Some(&format!("Available tokens: {}", TEMPLATE_TOKENS)));
+    tokens_label.set_xalign(0.0);
+    tokens_label.add_css_class("dim-label");
+    vbox.append(&tokens_label);
+
+    // --- Preview ---
+    let preview = TextView::new();
+    preview.set_editable(false);
+    preview.set_monospace(true);
+    preview.set_wrap_mode(WordChar);
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_vexpand(true);
+    scrolled.set_min_content_height(250);
+    scrolled.set_child(Some(&preview));
+    vbox.append(&scrolled);
+    dialog.set_extra_child(Some(&vbox));
+
+    // Recompute the plan and its preview whenever the template changes.
+    let plan: Rc<RefCell<Vec<PlannedRename>>> = Rc::new(RefCell::new(Vec::new()));
+    let refresh = Rc::new(clone!(@strong plan, @weak template_entry, @weak preview, @weak dialog => move || {
+        let new_plan = plan_renames(&items, &roots, &template_entry.text());
+        let text: Vec<String> = new_plan.iter().map(|planned| {
+            let old_name = planned.source.file_name().unwrap_or_default().to_string_lossy();
+            let new_name = planned.destination.file_name().unwrap_or_default().to_string_lossy();
+            match &planned.refused {
+                Some(reason) => format!("[refused] {}\n    → {} ({})", old_name, new_name, reason),
+                None => format!("{}\n    → {}", old_name, new_name),
+            }
+        }).collect();
+        preview.buffer().set_text(&text.join("\n"));
+        dialog.set_response_enabled("rename", new_plan.iter().any(|planned| planned.refused.is_none()));
+        *plan.borrow_mut() = new_plan;
+    }));
+    refresh();
+    template_entry.connect_changed(clone!(@strong refresh => move |_| refresh()));
+
+    dialog.connect_response(None, clone!(@weak window, @strong plan, @strong list_store, @strong app_state => move |_, response| {
+        if response == "rename" {
+            let renames: Vec<PlannedRename> = plan.borrow().iter().filter(|planned| planned.refused.is_none()).cloned().collect();
+            execute_renames(&window, renames, &list_store, &app_state);
+        }
+    }));
+    dialog.present();
+}
+
+/// Renames the folders on a background thread and points the affected results at their new locations.
+///
+/// Folders outside the scan roots (see `confine_to_roots`) are not renamed, and nothing is
+/// renamed in read-only mode. Every rename is recorded in the journal, which measures the
+/// size of each folder first, so the renames do not run on the main thread.
+fn execute_renames(window: &ApplicationWindow, renames: Vec<PlannedRename>, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let Ok(roots) = app_state.lock().map(|state| state.roots.clone()) else {
+        return;
+    };
+    let read_only = current_settings().read_only;
+    let (done_tx, done_rx) = channel();
+    spawn(move || {
+        let outcomes: Vec<(PlannedRename, Result<(), String>)> = renames.into_iter()
+            .map(|planned| {
+
+                // Check again right before renaming, as the preview may be outdated.
+                let outcome = if read_only {
+                    Err(READ_ONLY_MESSAGE.to_string())
+                } else if planned.destination.exists() {
+                    Err("a folder with this name already exists".to_string())
+                } else {
+                    confine_to_roots(&planned.source, &roots)
+                        .and_then(|_| {
+                            journaled(Operation::Rename, &planned.source, Some(&planned.destination), || rename(&planned.source, &planned.destination))
+                                .map_err(|err| err.to_string())
+                        })
+                };
+                (planned, outcome)
+            })
+            .collect();
+        done_tx.send(outcomes).ok();
+    });
+    timeout_add_local(Duration::from_millis(100), clone!(@weak window, @strong list_store, @strong app_state => @default-return Break, move || {
+        let Ok(outcomes) = done_rx.try_recv() else {
+            return Continue;
+        };
+        let mut failures = Vec::new();
+        if let Ok(mut state) = app_state.lock() {
+
+            // The results before their first rename, by index; nested folders rename a result twice.
+            let mut previous = BTreeMap::new();
+            for (planned, outcome) in outcomes {
+                if let Err(err) = outcome {
+                    failures.push(format!("{}: {}", planned.source.display(), err));
+                    continue;
+                }
+                for (index, result) in state.results.iter_mut().enumerate() {
+                    if let Some(new_path) = rebase_path(&result.path, &planned.source, &planned.destination) {
+                        previous.entry(index).or_insert_with(|| result.clone());
+                        result.path = new_path;
+                    }
+                }
+            }
+            let changed: Vec<DRResult> = previous.keys().map(|&index| state.results[index].clone()).collect();
+            update_rows(&list_store, &previous.into_values().collect::<Vec<_>>(), &changed);
+        }
+        if !failures.is_empty() {
+            report_problem_with_details(&window, &format!("Could not rename {} folder(s)", failures.len()), failures.join("\n"));
+        }
+        Break
+    }));
+}
@@ -0,0 +1,142 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use gio::ListStore;
+use glib::clone;
+use gtk4::{Box, Button, Label, ListBox, MenuButton, Orientation::{Horizontal, Vertical}, Popover, SelectionMode};
+use gtk4::pango::EllipsizeMode::Start;
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{BoxExt, ButtonExt, PopoverExt, WidgetExt};
+
+use crate::data::app_state::{AppState, DRResult};
+use crate::dr_analyzer::analyzer::update_ui;
+use crate::file_manager::file_ops::{collect_root_files, root_contains};
+use crate::ui::dialogs::show_error_dialog;
+
+/// Creates the header bar button that lists the active scan roots.
+///
+/// The button opens a popover containing a `ListBox` with one row per root. The list box is
+/// returned separately so it can be refreshed with `refresh_roots_list` whenever roots change.
+pub fn create_roots_button() -> (MenuButton, ListBox) {
+    let roots_list = ListBox::new();
+    roots_list.set_selection_mode(SelectionMode::None);
+    roots_list.add_css_class("boxed-list");
+    let placeholder = Label::new(Some("No folders added"));
+    placeholder.add_css_class("dim-label");
+    placeholder.set_margin_top(10);
+    placeholder.set_margin_bottom(10);
+    roots_list.set_placeholder(Some(&placeholder));
+
+    let vbox = Box::new(Vertical, 10);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+    vbox.set_size_request(350, -1);
+    let title = Label::new(Some("Scan Roots"));
+    title.add_css_class("heading");
+    vbox.append(&title);
+    vbox.append(&roots_list);
+
+    let popover = Popover::new();
+    popover.set_child(Some(&vbox));
+    let roots_button = MenuButton::new();
+    roots_button.set_icon_name("folder-symbolic");
+    roots_button.set_tooltip_text(Some("Scan Roots"));
+    roots_button.set_popover(Some(&popover));
+    (roots_button, roots_list)
+}
+
+/// Rebuilds the rows of the scan roots popover from the current `AppState`.
+///
+/// Each row shows the root's path and a button that removes the root together with
+/// all results found beneath it.
+pub fn refresh_roots_list(roots_list: &ListBox, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    while let Some(row) = roots_list.row_at_index(0) {
+        roots_list.remove(&row);
+    }
+    let roots = match app_state.lock() {
+        Ok(state) => state.roots.clone(),
+        Err(_) => return,
+    };
+    for root in roots {
+        let hbox = Box::new(Horizontal, 10);
+        hbox.set_margin_top(5);
+        hbox.set_margin_bottom(5);
+        hbox.set_margin_start(10);
+        hbox.set_margin_end(5);
+        let label = Label::new(Some(&root.to_string_lossy()));
+        label.set_hexpand(true);
+        label.set_xalign(0.0);
+        label.set_ellipsize(Start); // Keep the most specific part of the path visible.
+        label.set_tooltip_text(Some(&root.to_string_lossy()));
+        let remove_button = Button::from_icon_name("list-remove-symbolic");
+        remove_button.set_tooltip_text(Some("Remove Root"));
+        remove_button.add_css_class("flat");
+        hbox.append(&label);
+        hbox.append(&remove_button);
+        roots_list.append(&hbox);
+
+        remove_button.connect_clicked(clone!(@weak roots_list, @strong list_store, @strong app_state => move |_| {
+            remove_scan_root(&root, &list_store, &app_state);
+            refresh_roots_list(&roots_list, &list_store, &app_state);
+        }));
+    }
+}
+
+/// Adds folders, archives or individual log files as scan roots.
+///
+/// The logs found beneath each new root are merged into the existing results as pending
+/// entries; paths that are already listed are left untouched. Roots that cannot be read
+/// are reported with an error dialog and skipped.
+pub fn add_scan_roots(window: &ApplicationWindow, paths: Vec<PathBuf>, roots_list: &ListBox,
+                      list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    if let Ok(mut state) = app_state.lock() {
+        let report_problem = state.report_problem.clone();
+        let mut known: HashSet<PathBuf> = state.results.iter().map(|result| result.path.clone()).collect();
+        for root in paths {
+            if state.roots.contains(&root) {
+                continue;
+            }
+            let files = match collect_root_files(&root, &report_problem) {
+                Ok(files) => files,
+                Err(err) => {
+                    show_error_dialog(window, &format!("Error reading {}: {}", root.display(), err));
+                    continue;
+                }
+            };
+
+            // Create pending results for all files that are not yet in the list.
+            for path in files {
+                if !known.insert(path.clone()) {
+                    continue;
+                }
+                state.results.push(DRResult {
+                    filename: path.file_name().unwrap().to_string_lossy().into_owned(),
+                    path,
+                    dr_value: None,
+                    scanned: false, // Mark as unscanned initially.
+                    is_new: false,
+                    marked: false,
+                    invalid: false,
+                });
+            }
+            state.roots.push(root);
+        }
+        update_ui(list_store, &state.results);
+    }
+    refresh_roots_list(roots_list, list_store, app_state);
+}
+
+/// Removes a scan root and every result that is not covered by one of the remaining roots.
+pub fn remove_scan_root(root: &Path, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    if let Ok(mut state) = app_state.lock() {
+        state.roots.retain(|r| r != root);
+        let remaining = state.roots.clone();
+        state.results.retain(|result| {
+            !root_contains(root, &result.path) || remaining.iter().any(|r| root_contains(r, &result.path))
+        });
+        update_ui(list_store, &state.results);
+    }
+}
@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use gio::{ListStore, SimpleAction, SimpleActionGroup};
+use glib::{clone, Object, VariantTy};
+use glib::Propagation::Stop;
+use gtk4::{Box, CallbackAction, CheckButton, CustomFilter, FilterChange, FilterListModel, Label, Orientation::Horizontal, Scale, SearchBar, SearchEntry, Shortcut, ShortcutController, ShortcutTrigger, StringObject, ToggleButton, Widget};
+use libadwaita::prelude::{ActionMapExt, BoxExt, Cast, CheckButtonExt, EditableExt, FilterExt, ListModelExt, RangeExt, ScaleExt, ToggleButtonExt, WidgetExt};
+use regex::{Regex, RegexBuilder};
+
+/// The upper end of the DR range sliders. At this value, the range has no upper bound.
+const FILTER_DR_MAX: u8 = 20;
+
+/// The criteria of the search bar that decide which rows are shown.
+#[derive(Clone, Debug)]
+struct ResultFilter {
+    /// The lowercase query matched against file names and paths.
+    query: String,
+    /// The compiled query if regular expressions are enabled and the query is valid.
+    regex: Option<Regex>,
+    dr_min: u8,
+    dr_max: u8,
+    show_ok: bool,
+    show_err: bool,
+    show_pending: bool,
+    show_invalid: bool,
+}
+
+impl Default for ResultFilter {
+    fn default() -> Self {
+        ResultFilter {
+            query: String::new(),
+            regex: None,
+            dr_min: 0,
+            dr_max: FILTER_DR_MAX,
+            show_ok: true,
+            show_err: true,
+            show_pending: true,
+            show_invalid: true,
+        }
+    }
+}
+
+impl ResultFilter {
+
+    /// Checks whether a row, encoded as "filename\tpath\tdr\t…", passes the filter.
+    ///
+    /// The DR range only applies to rows with a DR value.
+    fn matches(&self, text: &str) -> bool {
+        let mut fields = text.split('\t');
+        let (filename, path, dr) = (fields.next().unwrap_or(""), fields.next().unwrap_or(""), fields.next().unwrap_or(""));
+        let status_matches = match dr {
+            "PENDING" => self.show_pending,
+            "ERR" => self.show_err,
+            "INVALID" => self.show_invalid,
+            dr => self.show_ok && dr.parse::<u8>().is_ok_and(|dr| {
+                dr >= self.dr_min && (self.dr_max >= FILTER_DR_MAX || dr <= self.dr_max)
+            }),
+        };
+        let query_matches = match &self.regex {
+            Some(regex) => regex.is_match(filename) || regex.is_match(path),
+            None => self.query.is_empty()
+                || filename.to_lowercase().contains(&self.query)
+                || path.to_lowercase().contains(&self.query),
+        };
+        status_matches && query_matches
+    }
+}
+
+/// Creates the search bar that filters the results list, toggled with Ctrl+F.
+///
+/// The bar filters by a case-insensitive substring of the file name or path, or by a regular
+/// expression if "Regex" is checked (an invalid expression is highlighted and ignored). Two
+/// sliders limit the DR range of scanned logs, and toggles show or hide logs by status.
+/// The filter is applied through `filter_model`; `file_count_label` shows how many of the
+/// results are shown. Closing the bar resets all filters.
+///
+/// The bar belongs to a workspace (see `add_workspace`) and adds its actions to the "tab" action
+/// group `actions` of it: "tab.search" opens the bar, and "tab.filter-dr" opens it and shows only
+/// the logs with the given DR value.
+pub fn create_search_bar(workspace: &Widget, actions: &SimpleActionGroup, filter_model: &FilterListModel, list_store: &ListStore, file_count_label: &Label) -> SearchBar {
+    let hbox = Box::new(Horizontal, 10);
+
+    // --- Query ---
+    let search_entry = SearchEntry::new();
+    search_entry.set_hexpand(true);
+    search_entry.set_placeholder_text(Some("Filter by file name or path"));
+    let regex_button = CheckButton::with_label("Regex");
+    hbox.append(&search_entry);
+    hbox.append(&regex_button);
+
+    // --- DR range ---
+    let min_scale = Scale::with_range(Horizontal, 0.0, FILTER_DR_MAX as f64, 1.0);
+    let max_scale = Scale::with_range(Horizontal, 0.0, FILTER_DR_MAX as f64, 1.0);
+    for scale in [&min_scale, &max_scale] {
+        scale.set_digits(0);
+        scale.set_draw_value(true);
+        scale.set_size_request(120, -1);
+    }
+    max_scale.set_value(FILTER_DR_MAX as f64);
+    max_scale.set_tooltip_text(Some("At the maximum, the range has no upper bound"));
+    hbox.append(&Label::new(Some("DR")));
+    hbox.append(&min_scale);
+    hbox.append(&Label::new(Some("to")));
+    hbox.append(&max_scale);
+
+    // --- Status toggles ---
+    let status_box = Box::new(Horizontal, 0);
+    status_box.add_css_class("linked");
+    let status_buttons: Vec<ToggleButton> = ["OK", "ERR", "PENDING", "INVALID"].iter().map(|label| {
+        let button = ToggleButton::with_label(label);
+        button.set_active(true);
+        status_box.append(&button);
+        button
+    }).collect();
+    hbox.append(&status_box);
+
+    let search_bar = SearchBar::new();
+    search_bar.set_child(Some(&hbox));
+    search_bar.connect_entry(&search_entry);
+    search_bar.set_key_capture_widget(Some(workspace));
+    search_bar.set_show_close_button(true);
+
+    // The filter reads the current criteria, which are rebuilt whenever a widget changes.
+    let criteria = Rc::new(RefCell::new(ResultFilter::default()));
+    let filter = CustomFilter::new(clone!(@strong criteria => move |item: &Object| {
+        item.downcast_ref::<StringObject>().is_some_and(|row| criteria.borrow().matches(&row.string()))
+    }));
+    filter_model.set_filter(Some(&filter));
+    let toggles = status_buttons.clone();
+    let status_toggles = status_buttons.clone();
+    let update = Rc::new(clone!(@strong criteria, @weak filter, @weak search_entry, @weak regex_button, @weak min_scale, @weak max_scale => move || {
+        let query = search_entry.text().to_string();
+        let regex = if regex_button.is_active() && !query.is_empty() {
+            RegexBuilder::new(&query).case_insensitive(true).build().ok()
+        } else {
+            None
+        };
+        if regex_button.is_active() && !query.is_empty() && regex.is_none() {
+            search_entry.add_css_class("error");
+        } else {
+            search_entry.remove_css_class("error");
+        }
+        *criteria.borrow_mut() = ResultFilter {
+            query: query.to_lowercase(),
+            regex,
+            dr_min: min_scale.value() as u8,
+            dr_max: max_scale.value() as u8,
+            show_ok: toggles[0].is_active(),
+            show_err: toggles[1].is_active(),
+            show_pending: toggles[2].is_active(),
+            show_invalid: toggles[3].is_active(),
+        };
+        filter.changed(FilterChange::Different);
+    }));
+    search_entry.connect_search_changed(clone!(@strong update => move |_| update()));
+    regex_button.connect_toggled(clone!(@strong update => move |_| update()));
+    for button in &status_buttons {
+        button.connect_toggled(clone!(@strong update => move |_| update()));
+    }
+
+    // Keep the range valid by moving the other slider along.
+    min_scale.connect_value_changed(clone!(@strong update, @weak max_scale => move |min_scale| {
+        if min_scale.value() > max_scale.value() {
+            max_scale.set_value(min_scale.value());
+        }
+        update();
+    }));
+    max_scale.connect_value_changed(clone!(@strong update, @weak min_scale => move |max_scale| {
+        if max_scale.value() < min_scale.value() {
+            min_scale.set_value(max_scale.value());
+        }
+        update();
+    }));
+
+    // Closing the bar shows all results again.
+    search_bar.connect_search_mode_enabled_notify(clone!(@weak search_entry, @weak regex_button, @weak min_scale, @weak max_scale => move |search_bar| {
+        if search_bar.is_search_mode() {
+            return;
+        }
+        search_entry.set_text("");
+        regex_button.set_active(false);
+        min_scale.set_value(0.0);
+        max_scale.set_value(FILTER_DR_MAX as f64);
+        for button in &status_buttons {
+            button.set_active(true);
+        }
+    }));
+
+    // Show how many of the results pass the filter.
+    let update_count = clone!(@weak file_count_label, @weak list_store => move |filter_model: &FilterListModel| {
+        file_count_label.set_text(&format!("Files: {} / {}", filter_model.n_items(), list_store.n_items()));
+    });
+    update_count(filter_model);
+    filter_model.connect_items_changed(move |filter_model, _, _, _| update_count(filter_model));
+
+    // Other views, such as the statistics dashboard, can show only the logs with one DR value.
+    // At the maximum of the sliders, this includes all higher values.
+    let filter_dr_action = SimpleAction::new("filter-dr", Some(VariantTy::BYTE));
+    filter_dr_action.connect_activate(clone!(@weak search_bar, @weak search_entry, @weak regex_button, @weak min_scale, @weak max_scale => move |_, parameter| {
+        let Some(dr) = parameter.and_then(|parameter| parameter.get::<u8>()) else {
+            return;
+        };
+        search_bar.set_search_mode(true);
+        search_entry.set_text("");
+        regex_button.set_active(false);
+        min_scale.set_value(dr.min(FILTER_DR_MAX) as f64);
+        max_scale.set_value(dr.min(FILTER_DR_MAX) as f64);
+        for (index, button) in status_toggles.iter().enumerate() {
+            button.set_active(index == 0); // Only "OK" logs have a DR value.
+        }
+    }));
+    actions.add_action(&filter_dr_action);
+
+    // Opens the search bar and moves the focus to the query, e.g. with "/" (see `setup_vim_controls`).
+    let search_action = SimpleAction::new("search", None);
+    search_action.connect_activate(clone!(@weak search_bar, @weak search_entry => move |_, _| {
+        search_bar.set_search_mode(true);
+        search_entry.grab_focus();
+    }));
+    actions.add_action(&search_action);
+
+    // Ctrl+F toggles the search bar.
+    let shortcut_controller = ShortcutController::new();
+    shortcut_controller.add_shortcut(Shortcut::new(
+        ShortcutTrigger::parse_string("<Control>f"),
+        Some(CallbackAction::new(clone!(@weak search_bar => @default-return Stop, move |_, _| {
+            search_bar.set_search_mode(!search_bar.is_search_mode());
+            Stop
+        }))),
+    ));
+    workspace.add_controller(shortcut_controller);
+
+    search_bar
+}
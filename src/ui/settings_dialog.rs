@@ -1,115 +1,127 @@
-use std::sync::{Arc, Mutex};
-
 use glib::{clone, idle_add_local_once};
 use glib::Propagation::{Proceed, Stop};
-use gtk4::{Box, Dialog, Label, Switch};
-use gtk4::Orientation::{Horizontal, Vertical};
-use libadwaita::{ApplicationWindow};
-use libadwaita::prelude::{BoxExt, DialogExt, GtkWindowExt, WidgetExt};
+use gtk4::{Align::Center, Switch};
+use libadwaita::{ActionRow, ApplicationWindow, PreferencesGroup, PreferencesPage, PreferencesWindow};
+use libadwaita::prelude::{ActionRowExt, GtkWindowExt, PreferencesGroupExt, PreferencesPageExt, PreferencesRowExt, PreferencesWindowExt, WidgetExt};
 
-use crate::data::app_state::AppState;
+use crate::data::settings::{current_settings, update_settings};
+use crate::ui::tier_editor::create_tier_editor;
 
-/// Displays a modal dialog for configuring application settings.
+/// Displays a modal preferences window for configuring application settings.
 ///
-/// This dialog provides options to control file deletion behavior:
-/// 1.  **Delete files from system**: A `Switch` to enable or disable permanent file deletion
-///     when items are removed from the list.
-/// 2.  **Also delete parent folders**: A dependent `Switch` that, when enabled, also removes
+/// The "General" page groups the options that control file deletion behavior:
+/// 1.  **Delete files from system**: A `Switch` to enable or disable file deletion
+///     when items are removed from the list. Deleted files are moved to the trash.
+/// 2.  **Delete permanently**: A dependent `Switch` that opts into removing files for good
+///     instead of moving them to the trash. Like the folder option, it is marked as "DANGEROUS".
+/// 3.  **Also delete parent folders**: A dependent `Switch` that, when enabled, also removes
 ///     a file's parent directory if it becomes empty after the file is deleted. This option
 ///     is marked as "DANGEROUS" and is only sensitive when the main deletion switch is active.
 ///
-/// The state of the switches is synchronized with the shared `AppState`.
-pub fn show_settings_dialog(window: &ApplicationWindow, app_state: &Arc<Mutex<AppState>>) {
+/// The switches edit the settings of the whole application, so they apply to every workspace
+/// and window (see `current_settings`).
+///
+/// The "DR Tiers" page edits the DR tiers shown in the results (see `create_tier_editor`).
+pub fn show_settings_dialog(window: &ApplicationWindow) {
 
-    // Create the modal dialog.
-    let dialog = Dialog::new();
-    dialog.set_title(Some("Settings"));
-    dialog.set_transient_for(Some(window));
-    dialog.set_modal(true);
-    dialog.set_default_size(400, 300);
+    // Create the modal preferences window.
+    let preferences = PreferencesWindow::new();
+    preferences.set_title(Some("Settings"));
+    preferences.set_transient_for(Some(window));
+    preferences.set_modal(true);
+    preferences.set_search_enabled(false);
+    preferences.set_default_size(600, 550);
 
-    // Set up the main content area with padding.
-    let content_area = dialog.content_area();
-    let vbox = Box::new(Vertical, 10);
-    vbox.set_margin_top(10);
-    vbox.set_margin_bottom(10);
-    vbox.set_margin_start(10);
-    vbox.set_margin_end(10);
+    // --- "Deleting Files" group ---
+    let general_page = PreferencesPage::new();
+    general_page.set_title("General");
+    general_page.set_icon_name(Some("preferences-system-symbolic"));
+    let delete_group = PreferencesGroup::new();
+    delete_group.set_title("Deleting Files");
+    delete_group.set_description(Some("What happens on disk when logs are removed from the list"));
+    let (row, switch) = switch_row("Delete files from system", "Removing logs from the list also moves them to the trash");
+    let (permanent_row, permanent_switch) = switch_row("Delete permanently (DANGEROUS)", "Remove files for good instead of moving them to the trash");
+    let (folder_row, folder_switch) = switch_row("Also delete parent folders (DANGEROUS)", "Remove a log's folder when it becomes empty");
+    delete_group.add(&row);
+    delete_group.add(&permanent_row);
+    delete_group.add(&folder_row);
+    general_page.add(&delete_group);
 
-    // --- "Delete files" setting ---
-    let hbox = Box::new(Horizontal, 10);
-    let label = Label::new(Some("Delete files from system when removing from list"));
-    label.set_hexpand(true);
-    label.set_xalign(0.0);
-    let switch = Switch::new();
-    hbox.append(&label);
-    hbox.append(&switch);
-    vbox.append(&hbox);
+    // Initialize switch states from the settings.
+    let settings = current_settings();
+    switch.set_active(settings.delete_files);
+    folder_switch.set_active(settings.delete_folders);
+    permanent_switch.set_active(settings.delete_permanently);
+    folder_row.set_sensitive(settings.delete_files);
+    permanent_row.set_sensitive(settings.delete_files);
 
-    // --- "Delete parent folders" setting ---
-    let folder_hbox = Box::new(Horizontal, 10);
-    let folder_label = Label::new(Some("Also delete parent folders (DANGEROUS)"));
-    folder_label.set_hexpand(true);
-    folder_label.set_xalign(0.0);
-    let folder_switch = Switch::new();
-    folder_hbox.append(&folder_label);
-    folder_hbox.append(&folder_switch);
-    vbox.append(&folder_hbox);
-    
-    // Initialize switch states from the application state.
-    if let Ok(state) = app_state.lock() {
-        switch.set_active(state.delete_files);
-        folder_switch.set_active(state.delete_folders);
-        folder_switch.set_sensitive(state.delete_files);
-        
-        if state.delete_files {
-            folder_label.remove_css_class("dim-label");
-        } else {
-            folder_label.add_css_class("dim-label");
-        }
-    }
+    // Connect the main "Delete files" switch to update the settings and UI.
+    switch.connect_state_set(clone!(@strong folder_switch, @strong folder_row, @strong permanent_switch, @strong permanent_row => move |_, active| {
+        update_settings(|settings| {
+            settings.delete_files = active;
 
-    // Connect the main "Delete files" switch to update state and UI.
-    switch.connect_state_set(clone!(@strong app_state, @strong folder_switch, @strong folder_label => move |_, active| {
-        if let Ok(mut state) = app_state.lock() {
-            state.delete_files = active;
-            
-            // If the main switch is turned off, also turn off and disable the folder switch.
+            // If the main switch is turned off, also turn off the dependent settings.
             if !active {
-                state.delete_folders = false;
-                
-                // Defer UI updates to avoid deadlocks and ensure they run on the main thread.
-                idle_add_local_once(clone!(@strong folder_switch, @strong folder_label => move || {
-                    folder_switch.set_active(false);
-                    folder_switch.set_sensitive(false);
-                    folder_label.add_css_class("dim-label");
-                }));
-            } else {
-                // If turned on, just enable the folder switch.
-                idle_add_local_once(clone!(@strong folder_switch, @strong folder_label => move || {
-                    folder_switch.set_sensitive(true);
-                    folder_label.remove_css_class("dim-label");
-                }));
+                settings.delete_folders = false;
+                settings.delete_permanently = false;
             }
-        }
+        });
+
+        // Defer UI updates until the state change of this switch has been handled.
+        idle_add_local_once(clone!(@strong folder_switch, @strong folder_row, @strong permanent_switch, @strong permanent_row => move || {
+            if !active {
+                folder_switch.set_active(false);
+                permanent_switch.set_active(false);
+            }
+            folder_row.set_sensitive(active);
+            permanent_row.set_sensitive(active);
+        }));
         Proceed // Allow the state change to proceed.
     }));
 
     // Connect the "Delete parent folders" switch.
-    folder_switch.connect_state_set(clone!(@strong app_state => move |_, active| {
-        if let Ok(mut state) = app_state.lock() {
-            // Only allow changing the folder switch if the main delete switch is active.
-            if state.delete_files {
-                state.delete_folders = active;
-                Proceed // Allow the state change.
-            } else {
-                Stop // Prevent the state change.
-            }
+    folder_switch.connect_state_set(|_, active| {
+        // Only allow changing the folder switch if the main delete switch is active.
+        if current_settings().delete_files {
+            update_settings(|settings| settings.delete_folders = active);
+            Proceed // Allow the state change.
         } else {
-            Stop // Prevent change if the lock fails.
+            Stop // Prevent the state change.
         }
-    }));
+    });
+
+    // Connect the "Delete permanently" switch.
+    permanent_switch.connect_state_set(|_, active| {
+        // Only allow permanent deletion if the main delete switch is active.
+        if current_settings().delete_files {
+            update_settings(|settings| settings.delete_permanently = active);
+            Proceed // Allow the state change.
+        } else {
+            Stop // Prevent the state change.
+        }
+    });
+
+    // --- DR tiers ---
+    let tiers_page = PreferencesPage::new();
+    tiers_page.set_title("DR Tiers");
+    tiers_page.set_icon_name(Some("applications-graphics-symbolic"));
+    let tiers_group = PreferencesGroup::new();
+    tiers_group.add(&create_tier_editor());
+    tiers_page.add(&tiers_group);
 
-    content_area.append(&vbox);
-    dialog.show();
-}
\ No newline at end of file
+    preferences.add(&general_page);
+    preferences.add(&tiers_page);
+    preferences.present();
+}
+
+/// Creates a preferences row with a title, a subtitle and a switch that toggles with the row.
+fn switch_row(title: &str, subtitle: &str) -> (ActionRow, Switch) {
+    let row = ActionRow::new();
+    row.set_title(title);
+    row.set_subtitle(subtitle);
+    let switch = Switch::new();
+    switch.set_valign(Center);
+    row.add_suffix(&switch);
+    row.set_activatable_widget(Some(&switch));
+    (row, switch)
+}
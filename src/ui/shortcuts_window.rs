@@ -0,0 +1,154 @@
+use gtk4::{Builder, ShortcutsWindow};
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{ApplicationWindowExt, GtkApplicationExt, GtkWindowExt};
+
+/// The description of the shortcuts window, listing every keyboard shortcut of the main window.
+///
+/// GTK only allows sections, groups and shortcuts to be added to a `ShortcutsWindow` from a
+/// UI description, so it is built with a `Builder`.
+const SHORTCUTS_UI: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<interface>
+  <object class="GtkShortcutsWindow" id="shortcuts_window">
+    <property name="modal">true</property>
+    <child>
+      <object class="GtkShortcutsSection">
+        <property name="section-name">main</property>
+        <property name="max-height">12</property>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">Navigation</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Next row</property>
+                <property name="accelerator">j</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Previous row</property>
+                <property name="accelerator">k</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">First row</property>
+                <property name="accelerator">g</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Last row</property>
+                <property name="accelerator">&lt;Shift&gt;g</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Select all rows</property>
+                <property name="accelerator">&lt;Control&gt;a</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Search the results</property>
+                <property name="accelerator">slash</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Toggle the search bar</property>
+                <property name="accelerator">&lt;Control&gt;f</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Show or hide the log details</property>
+                <property name="accelerator">space</property>
+              </object>
+            </child>
+          </object>
+        </child>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">Logs</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Open the selected logs</property>
+                <property name="accelerator">Return</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Mark or unmark the selected logs</property>
+                <property name="accelerator">x</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Remove the selected logs</property>
+                <property name="accelerator">d&amp;d Delete</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Move the selected logs</property>
+                <property name="accelerator">&lt;Control&gt;m</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Rename the album folders</property>
+                <property name="accelerator">F2</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Remove the album folders</property>
+                <property name="accelerator">&lt;Shift&gt;Delete</property>
+              </object>
+            </child>
+          </object>
+        </child>
+        <child>
+          <object class="GtkShortcutsGroup">
+            <property name="title">General</property>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">New tab</property>
+                <property name="accelerator">&lt;Control&gt;t</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Close tab</property>
+                <property name="accelerator">&lt;Control&gt;w</property>
+              </object>
+            </child>
+            <child>
+              <object class="GtkShortcutsShortcut">
+                <property name="title">Keyboard shortcuts</property>
+                <property name="accelerator">&lt;Control&gt;question</property>
+              </object>
+            </child>
+          </object>
+        </child>
+      </object>
+    </child>
+  </object>
+</interface>
+"#;
+
+/// Sets up the keyboard shortcuts window of the main window, shown with Ctrl+?.
+///
+/// The window is the help overlay of the main window, so it is shown by its
+/// "win.show-help-overlay" action.
+pub fn setup_shortcuts_window(window: &ApplicationWindow) {
+    let builder = Builder::from_string(SHORTCUTS_UI);
+    let Some(shortcuts_window) = builder.object::<ShortcutsWindow>("shortcuts_window") else {
+        return;
+    };
+    shortcuts_window.set_transient_for(Some(window));
+    window.set_help_overlay(Some(&shortcuts_window));
+    if let Some(app) = window.application() {
+        app.set_accels_for_action("win.show-help-overlay", &["<Control>question"]);
+    }
+}
@@ -0,0 +1,169 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use gio::ListStore;
+use glib::{clone, timeout_add_local_once, SourceId};
+use glib::Propagation::Proceed;
+use gtk4::{Box, DrawingArea, GestureClick, Grid, Label, Orientation::{Horizontal, Vertical}, Widget, Window};
+use gtk4::cairo::Context;
+use libadwaita::ApplicationWindow;
+use libadwaita::prelude::{BoxExt, DrawingAreaExt, DrawingAreaExtManual, GridExt, GtkWindowExt, ListModelExt, ObjectExt, StyleContextExt, ToVariant, WidgetExt};
+
+use crate::data::app_state::AppState;
+use crate::data::statistics::{Statistics, HISTOGRAM_MAX};
+use crate::data::tiers::tier_color;
+
+/// How long the dashboard waits for the results to settle before it is recomputed.
+const REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+/// The space below the bars of the histogram, for the DR labels.
+const AXIS_HEIGHT: f64 = 20.0;
+
+/// Shows the statistics dashboard for the current results.
+///
+/// The dashboard shows a histogram of the DR values colored by their DR tier, the number of
+/// logs per DR tier, the mean and median DR, and the number of errors, invalid and
+/// pending logs. It follows the results while it is open. Clicking a bar filters the results
+/// list of `workspace` to that DR value through the "tab.filter-dr" action of its search bar.
+pub fn show_statistics_window(window: &ApplicationWindow, workspace: &Widget, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let dashboard = Window::new();
+    dashboard.set_title(Some("Statistics"));
+    dashboard.set_transient_for(Some(window));
+    dashboard.set_default_size(700, 500);
+
+    let vbox = Box::new(Vertical, 10);
+    vbox.set_margin_top(10);
+    vbox.set_margin_bottom(10);
+    vbox.set_margin_start(10);
+    vbox.set_margin_end(10);
+
+    let statistics = Rc::new(RefCell::new(Statistics::default()));
+    let histogram = DrawingArea::new();
+    histogram.set_content_height(250);
+    histogram.set_vexpand(true);
+    histogram.set_tooltip_text(Some("Click a bar to show only the logs with that DR value"));
+    histogram.set_draw_func(clone!(@strong statistics => move |area, context, width, height| {
+        draw_histogram(area, context, width as f64, height as f64, &statistics.borrow());
+    }));
+    vbox.append(&histogram);
+
+    // Clicking a bar filters the results to its DR value.
+    let gesture_click = GestureClick::new();
+    gesture_click.connect_released(clone!(@weak workspace, @weak histogram, @strong statistics => move |_, _n_press, x, _y| {
+        let bar_width = histogram.width() as f64 / (HISTOGRAM_MAX as f64 + 1.0);
+        let dr = (x / bar_width).floor().clamp(0.0, HISTOGRAM_MAX as f64) as u8;
+        if statistics.borrow().histogram.get(dr as usize).is_some_and(|&count| count > 0) {
+            let _ = workspace.activate_action("tab.filter-dr", Some(&dr.to_variant()));
+        }
+    }));
+    histogram.add_controller(gesture_click);
+
+    let hbox = Box::new(Horizontal, 30);
+    let tiers_grid = Grid::new();
+    tiers_grid.set_row_spacing(5);
+    tiers_grid.set_column_spacing(10);
+    let summary_grid = Grid::new();
+    summary_grid.set_row_spacing(5);
+    summary_grid.set_column_spacing(10);
+    hbox.append(&tiers_grid);
+    hbox.append(&summary_grid);
+    vbox.append(&hbox);
+
+    let refresh = Rc::new(clone!(@weak histogram, @weak tiers_grid, @weak summary_grid, @strong app_state, @strong statistics => move || {
+        let Ok(state) = app_state.lock() else {
+            return;
+        };
+        *statistics.borrow_mut() = Statistics::compute(&state.results);
+        let statistics = statistics.borrow();
+        let tier_rows: Vec<(String, String)> = statistics.tiers.iter().map(|(name, count)| (name.clone(), count.to_string())).collect();
+        fill_grid(&tiers_grid, &tier_rows);
+        let format_dr = |value: Option<f64>| value.map_or("—".to_string(), |value| format!("{:.1}", value));
+        fill_grid(&summary_grid, &[
+            ("Logs".to_string(), state.results.len().to_string()),
+            ("Mean DR".to_string(), format_dr(statistics.mean)),
+            ("Median DR".to_string(), format_dr(statistics.median)),
+            ("Errors (DRERR)".to_string(), statistics.errors.to_string()),
+            ("Invalid".to_string(), statistics.invalid.to_string()),
+            ("Pending".to_string(), statistics.pending.to_string()),
+        ]);
+        histogram.queue_draw();
+    }));
+    refresh();
+
+    // Follow the results, but only recompute once a burst of changes (e.g. a scan batch) is over.
+    let pending_refresh: Rc<RefCell<Option<SourceId>>> = Rc::new(RefCell::new(None));
+    let handler_id = list_store.connect_items_changed(clone!(@strong refresh, @strong pending_refresh => move |_, _, _, _| {
+        if pending_refresh.borrow().is_some() {
+            return;
+        }
+        let source_id = timeout_add_local_once(REFRESH_DELAY, clone!(@strong refresh, @strong pending_refresh => move || {
+            pending_refresh.borrow_mut().take();
+            refresh();
+        }));
+        *pending_refresh.borrow_mut() = Some(source_id);
+    }));
+    let handler_id = RefCell::new(Some(handler_id));
+    dashboard.connect_close_request(clone!(@weak list_store => @default-return Proceed, move |_| {
+        if let Some(handler_id) = handler_id.borrow_mut().take() {
+            list_store.disconnect(handler_id);
+        }
+        if let Some(source_id) = pending_refresh.borrow_mut().take() {
+            source_id.remove();
+        }
+        Proceed
+    }));
+
+    dashboard.set_child(Some(&vbox));
+    dashboard.present();
+}
+
+/// Replaces the contents of a grid with rows of a dimmed key and its value.
+fn fill_grid(grid: &Grid, rows: &[(String, String)]) {
+    while let Some(child) = grid.first_child() {
+        grid.remove(&child);
+    }
+    for (row, (key, value)) in rows.iter().enumerate() {
+        let key_label = Label::new(Some(key));
+        key_label.set_xalign(0.0);
+        key_label.add_css_class("dim-label");
+        let value_label = Label::new(Some(value));
+        value_label.set_xalign(1.0);
+        grid.attach(&key_label, 0, row as i32, 1, 1);
+        grid.attach(&value_label, 1, row as i32, 1, 1);
+    }
+}
+
+/// Draws one bar per DR value, scaled to the largest count, with the DR values below.
+fn draw_histogram(area: &DrawingArea, context: &Context, width: f64, height: f64, statistics: &Statistics) {
+    let foreground = area.style_context().color();
+    let bar_width = width / (HISTOGRAM_MAX as f64 + 1.0);
+    let max_count = statistics.histogram.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bars_height = (height - AXIS_HEIGHT).max(0.0);
+    context.set_font_size(11.0);
+    for (dr, &count) in statistics.histogram.iter().enumerate() {
+        let x = dr as f64 * bar_width;
+        let bar_height = count as f64 / max_count * (bars_height - AXIS_HEIGHT);
+        let (r, g, b) = tier_color(dr as u8);
+        context.set_source_rgb(r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+        context.rectangle(x + 2.0, bars_height - bar_height, (bar_width - 4.0).max(1.0), bar_height);
+        let _ = context.fill();
+
+        // The count above the bar and the DR value below it.
+        context.set_source_rgba(foreground.red() as f64, foreground.green() as f64, foreground.blue() as f64, foreground.alpha() as f64);
+        let label = if dr == HISTOGRAM_MAX as usize { format!("{}+", dr) } else { dr.to_string() };
+        draw_centered_text(context, &label, x + bar_width / 2.0, height - 5.0);
+        if count > 0 {
+            draw_centered_text(context, &count.to_string(), x + bar_width / 2.0, bars_height - bar_height - 5.0);
+        }
+    }
+}
+
+/// Draws `text` horizontally centered on `x`, with its baseline at `y`.
+fn draw_centered_text(context: &Context, text: &str, x: f64, y: f64) {
+    if let Ok(extents) = context.text_extents(text) {
+        context.move_to(x - extents.width() / 2.0, y);
+        let _ = context.show_text(text);
+    }
+}
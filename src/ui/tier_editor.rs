@@ -0,0 +1,194 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use glib::{clone, timeout_add_local_once, SourceId};
+use gtk4::{Box, Button, ColorButton, DropDown, Entry, Label, ListBox, ListBoxRow, Orientation::{Horizontal, Vertical}, ScrolledWindow, SelectionMode, SpinButton};
+use gtk4::gdk::RGBA;
+use libadwaita::prelude::{BoxExt, ButtonExt, CastNone, ColorChooserExt, EditableExt, ListBoxRowExt, WidgetExt};
+
+use crate::data::tiers::{current_tiers, default_tiers, set_current_tiers, DrTier, PALETTES};
+use crate::ui::column_view::refresh_dr_columns;
+use crate::ui::dialogs::report_problem;
+
+/// How long edits of a tier have to pause before they are saved and shown.
+const APPLY_DELAY: Duration = Duration::from_millis(500);
+
+/// The widgets editing a single DR tier.
+struct TierRow {
+    row: ListBoxRow,
+    min_dr: SpinButton,
+    name: Entry,
+    color: ColorButton,
+    remove_button: Button,
+}
+
+impl TierRow {
+
+    /// Creates the widgets for a tier, initialized from `tier`.
+    fn new(tier: &DrTier) -> Self {
+        let hbox = Box::new(Horizontal, 5);
+        hbox.set_margin_top(5);
+        hbox.set_margin_bottom(5);
+        hbox.set_margin_start(5);
+        hbox.set_margin_end(5);
+        let min_dr = SpinButton::with_range(0.0, 30.0, 1.0);
+        min_dr.set_value(tier.min_dr as f64);
+        min_dr.set_tooltip_text(Some("The lowest DR value of the tier; it reaches up to the next tier"));
+        let name = Entry::new();
+        name.set_text(&tier.name);
+        name.set_hexpand(true);
+        let (r, g, b) = tier.color;
+        let color = ColorButton::with_rgba(&RGBA::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0));
+        color.set_use_alpha(false);
+        let remove_button = Button::from_icon_name("list-remove-symbolic");
+        remove_button.set_tooltip_text(Some("Remove Tier"));
+        hbox.append(&Label::new(Some("From DR")));
+        hbox.append(&min_dr);
+        hbox.append(&name);
+        hbox.append(&color);
+        hbox.append(&remove_button);
+
+        let row = ListBoxRow::new();
+        row.set_activatable(false);
+        row.set_child(Some(&hbox));
+        remove_button.connect_clicked(clone!(@weak row => move |_| {
+            if let Some(list) = row.parent().and_downcast::<ListBox>() {
+                list.remove(&row);
+            }
+        }));
+        TierRow { row, min_dr, name, color, remove_button }
+    }
+
+    /// Reads the tier back from the widgets.
+    fn tier(&self) -> DrTier {
+        let rgba = self.color.rgba();
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        DrTier {
+            min_dr: self.min_dr.value_as_int().clamp(0, u8::MAX as i32) as u8,
+            name: self.name.text().trim().to_string(),
+            color: (channel(rgba.red()), channel(rgba.green()), channel(rgba.blue())),
+        }
+    }
+
+    /// Sets the color of the tier.
+    fn set_color(&self, (r, g, b): (u8, u8, u8)) {
+        self.color.set_rgba(&RGBA::new(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, 1.0));
+    }
+
+    /// Calls `changed` whenever one of the tier's widgets is edited or the tier is removed.
+    fn connect_changed(&self, changed: &Rc<dyn Fn()>) {
+        self.min_dr.connect_value_changed(clone!(@strong changed => move |_| changed()));
+        self.name.connect_changed(clone!(@strong changed => move |_| changed()));
+        self.color.connect_color_set(clone!(@strong changed => move |_| changed()));
+        self.remove_button.connect_clicked(clone!(@strong changed => move |_| changed()));
+    }
+}
+
+/// Creates the preferences section for editing the DR tiers.
+///
+/// Each tier has a lowest DR value, a name and a color, and reaches up to the next tier.
+/// A preset palette (see `PALETTES`) can be applied to color all tiers at once, from the
+/// lowest to the highest. Edits are saved and shown in the DR columns of all tabs once the
+/// user paused for `APPLY_DELAY`; the buttons apply their changes right away.
+pub fn create_tier_editor() -> Box {
+    let vbox = Box::new(Vertical, 10);
+    let heading = Label::new(Some("DR Tiers"));
+    heading.set_xalign(0.0);
+    heading.add_css_class("heading");
+    vbox.append(&heading);
+
+    let tiers_list = ListBox::new();
+    tiers_list.set_selection_mode(SelectionMode::None);
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_min_content_height(200);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&tiers_list));
+    vbox.append(&scrolled);
+
+    let buttons = Box::new(Horizontal, 5);
+    let add_button = Button::with_label("Add Tier");
+    let palette = DropDown::from_strings(&PALETTES.iter().map(|palette| palette.name).collect::<Vec<_>>());
+    let palette_button = Button::with_label("Apply Palette");
+    let reset_button = Button::with_label("Reset to Defaults");
+    let spacer = Box::new(Horizontal, 0);
+    spacer.set_hexpand(true);
+    buttons.append(&add_button);
+    buttons.append(&spacer);
+    buttons.append(&palette);
+    buttons.append(&palette_button);
+    buttons.append(&reset_button);
+    vbox.append(&buttons);
+
+    // The rows are kept alongside the list box, so that the tiers can be read back.
+    let rows: Rc<RefCell<Vec<TierRow>>> = Rc::new(RefCell::new(Vec::new()));
+    let pending_apply: Rc<RefCell<Option<SourceId>>> = Rc::new(RefCell::new(None));
+    let apply: Rc<dyn Fn()> = Rc::new(clone!(@strong rows, @strong pending_apply => move || {
+        if let Some(source_id) = pending_apply.borrow_mut().take() {
+            source_id.remove();
+        }
+        let tiers = rows.borrow().iter().filter(|row| row.row.parent().is_some()).map(TierRow::tier).collect();
+        if let Err(err) = set_current_tiers(tiers) {
+            report_problem(err);
+        }
+        refresh_dr_columns();
+    }));
+
+    // Edits of a tier are applied once the user stopped typing or stepping for a moment.
+    let schedule_apply: Rc<dyn Fn()> = Rc::new(clone!(@strong apply, @strong pending_apply => move || {
+        if let Some(source_id) = pending_apply.borrow_mut().take() {
+            source_id.remove();
+        }
+        let source_id = timeout_add_local_once(APPLY_DELAY, clone!(@strong apply, @strong pending_apply => move || {
+            pending_apply.borrow_mut().take();
+            apply();
+        }));
+        *pending_apply.borrow_mut() = Some(source_id);
+    }));
+    let add_row = Rc::new(clone!(@weak tiers_list, @strong rows, @strong schedule_apply => move |tier: &DrTier| {
+        let row = TierRow::new(tier);
+        row.connect_changed(&schedule_apply);
+        tiers_list.append(&row.row);
+        rows.borrow_mut().push(row);
+    }));
+    for tier in current_tiers() {
+        add_row(&tier);
+    }
+
+    add_button.connect_clicked(clone!(@strong add_row, @strong rows, @strong apply => move |_| {
+        let highest = rows.borrow().iter().filter(|row| row.row.parent().is_some()).map(|row| row.tier().min_dr).max();
+        add_row(&DrTier {
+            min_dr: highest.map_or(0, |dr| dr.saturating_add(1)),
+            name: "New Tier".to_string(),
+            color: (128, 128, 128),
+        });
+        apply();
+    }));
+
+    // Color the tiers from the lowest to the highest with the chosen palette.
+    palette_button.connect_clicked(clone!(@weak palette, @strong rows, @strong apply => move |_| {
+        let Some(chosen) = PALETTES.get(palette.selected() as usize) else {
+            return;
+        };
+        let rows = rows.borrow();
+        let mut shown: Vec<&TierRow> = rows.iter().filter(|row| row.row.parent().is_some()).collect();
+        shown.sort_by_key(|row| row.tier().min_dr);
+        for (row, color) in shown.iter().zip(chosen.colors(shown.len())) {
+            row.set_color(color);
+        }
+        drop(rows);
+        apply();
+    }));
+
+    reset_button.connect_clicked(clone!(@weak tiers_list, @strong add_row, @strong rows, @strong apply => move |_| {
+        for row in rows.borrow_mut().drain(..).filter(|row| row.row.parent().is_some()) {
+            tiers_list.remove(&row.row);
+        }
+        for tier in default_tiers() {
+            add_row(&tier);
+        }
+        apply();
+    }));
+
+    vbox
+}
@@ -0,0 +1,353 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use gio::SimpleActionGroup;
+use glib::clone;
+use gtk4::{Adjustment, Box, Button, ColumnView, Label, MultiSelection, Orientation::{Horizontal, Vertical}, Paned, ProgressBar, ScrolledWindow, Stack, ToggleButton, Viewport, Widget};
+use gtk4::Align::{Fill, Start};
+use gtk4::PolicyType::{Automatic, Never};
+use libadwaita::{ApplicationWindow, TabPage, TabView, ToastOverlay};
+use libadwaita::prelude::{BoxExt, ButtonExt, Cast, CastNone, IsA, ListModelExt, SelectionModelExt, ToggleButtonExt, WidgetExt};
+
+use crate::data::app_state::AppState;
+use crate::file_manager::file_ops::selected_paths;
+use crate::ui::album_dialog::show_remove_album_dialog;
+use crate::ui::column_view::{create_column_view, save_column_layout};
+use crate::ui::context_menu::setup_context_menu;
+use crate::ui::detail_pane::create_detail_pane;
+use crate::ui::dialogs::problem_handler;
+use crate::ui::folder_view::{create_folder_view, logs_beneath, selected_row_paths};
+use crate::ui::header_bar::{setup_button_actions, setup_read_only_indicator, setup_watch_button, WorkspaceControls};
+use crate::ui::history_window::show_history_window;
+use crate::ui::move_dialog::show_move_dialog;
+use crate::ui::policy_dialog::show_policy_dialog;
+use crate::ui::rename_dialog::show_rename_dialog;
+use crate::ui::scan_roots::create_roots_button;
+use crate::ui::search_bar::create_search_bar;
+use crate::ui::settings_dialog::show_settings_dialog;
+use crate::ui::statistics_window::show_statistics_window;
+use crate::utils::event_handlers::{setup_drop_target, setup_keyboard_controls, setup_mouse_controls, setup_vim_controls};
+
+/// The two views of the results of a workspace, of which one is shown at a time.
+///
+/// Keyboard shortcuts and toolbar buttons act on the view that is shown. In the folder view,
+/// the selected folders stand for all logs beneath them (see `logs_beneath`).
+#[derive(Clone)]
+pub struct ResultsViews {
+    /// The stack showing either the "list" or the "folders" view.
+    pub stack: Stack,
+    /// The flat results list, with one row per log.
+    pub list: ColumnView,
+    pub list_selection: MultiSelection,
+    /// The folder view, with one row per folder or log.
+    pub folders: ColumnView,
+    pub folder_selection: MultiSelection,
+}
+
+impl ResultsViews {
+
+    /// Returns `true` while the folder view is shown instead of the flat list.
+    pub fn folders_shown(&self) -> bool {
+        self.stack.visible_child_name().as_deref() == Some("folders")
+    }
+
+    /// Returns the column view and the selection of the view that is shown.
+    pub fn shown(&self) -> (&ColumnView, &MultiSelection) {
+        if self.folders_shown() {
+            (&self.folders, &self.folder_selection)
+        } else {
+            (&self.list, &self.list_selection)
+        }
+    }
+
+    /// Returns the paths of the logs selected in the view that is shown.
+    pub fn selected_logs(&self, app_state: &Arc<Mutex<AppState>>) -> Vec<PathBuf> {
+        if self.folders_shown() {
+            logs_beneath(&selected_row_paths(&self.folder_selection), app_state)
+        } else {
+            selected_paths(&self.list_selection)
+        }
+    }
+}
+
+/// Returns the window a widget of a workspace is currently shown in.
+///
+/// Workspaces can be moved to another window, so event handlers look the window up
+/// whenever they need it instead of keeping the window they were created in.
+pub fn workspace_window(widget: &impl IsA<Widget>) -> Option<ApplicationWindow> {
+    widget.root().and_downcast::<ApplicationWindow>()
+}
+
+/// Adds a new, empty workspace as a tab of `tab_view` and selects it.
+///
+/// Every workspace has its own `AppState`, so its scan roots, results, filters and scan
+/// progress are independent of the other tabs. It consists of a toolbar with the buttons
+/// for its roots, scans and file operations, the progress and search bars, the results with
+/// the detail pane below them, and the file count.
+///
+/// The actions of the workspace, such as the vim-like controls and the search, are in its
+/// "tab" action group; the window forwards its shortcuts to the selected tab. The tab title
+/// follows the scan roots, and the tab shows whether it is scanning or read-only. Read-only
+/// mode and the deletion settings apply to every workspace (see `current_settings`).
+pub fn add_workspace(tab_view: &TabView) -> TabPage {
+
+    // Non-fatal problems of this workspace, such as unreadable folders, are shown as toasts
+    // over its results, so that problems of a scan in a background tab stay with that tab.
+    let toast_overlay = ToastOverlay::new();
+
+    // Initialize the state of this workspace, protected by a Mutex for thread safety.
+    let app_state = Arc::new(Mutex::new(AppState {
+        results: Vec::new(),
+        roots: Vec::new(),
+        scan_control: None,
+        retention_rules: Vec::new(),
+        report_problem: problem_handler(&toast_overlay),
+    }));
+
+    // Create the toolbar with the buttons that act on this workspace.
+    let open_button = Button::from_icon_name("list-add-symbolic");
+    open_button.set_tooltip_text(Some("Select Directory"));
+    let scan_button = Button::from_icon_name("view-refresh-symbolic");
+    scan_button.set_tooltip_text(Some("Scan Files"));
+    let pause_button = ToggleButton::new();
+    pause_button.set_icon_name("media-playback-pause-symbolic");
+    pause_button.set_tooltip_text(Some("Pause Scan"));
+    pause_button.set_visible(false); // Only shown while a scan is running.
+    let clear_button = Button::from_icon_name("process-stop-symbolic");
+    clear_button.set_tooltip_text(Some("Clear List"));
+
+    // Add a button that moves the selected logs or albums into destination folders.
+    let move_button = Button::from_icon_name("document-send-symbolic");
+    move_button.set_tooltip_text(Some("Move Selected To…"));
+
+    // Add a button that renames the album folders of the selected logs from a template.
+    let rename_button = Button::from_icon_name("document-edit-symbolic");
+    rename_button.set_tooltip_text(Some("Rename Album Folders…"));
+
+    // Add a button that removes the whole album folders of the selected logs.
+    let remove_album_button = Button::from_icon_name("user-trash-symbolic");
+    remove_album_button.set_tooltip_text(Some("Remove Album Folders…"));
+
+    // Add a button listing the scan roots, each of which can be removed separately.
+    let (roots_button, roots_list) = create_roots_button();
+
+    // Add a toggle that switches the results between the flat list and the folder tree.
+    let folder_view_button = ToggleButton::new();
+    folder_view_button.set_icon_name("folder-symbolic");
+    folder_view_button.set_tooltip_text(Some("Folder View"));
+
+    // Add a toggle for watch mode, which picks up new logs while downloads finish.
+    let watch_button = ToggleButton::new();
+    watch_button.set_icon_name("emblem-synchronizing-symbolic");
+    watch_button.set_tooltip_text(Some("Watch for New Logs"));
+
+    // Add a button that opens the retention policy editor.
+    let policy_button = Button::from_icon_name("edit-clear-all-symbolic");
+    policy_button.set_tooltip_text(Some("Retention Policy…"));
+
+    // Add a button that opens the journal of file operations.
+    let history_button = Button::from_icon_name("document-open-recent-symbolic");
+    history_button.set_tooltip_text(Some("History"));
+
+    // Add a button that opens the statistics dashboard.
+    let statistics_button = Button::from_icon_name("utilities-system-monitor-symbolic");
+    statistics_button.set_tooltip_text(Some("Statistics"));
+
+    // Add a settings button with a gear icon.
+    let settings_button = Button::from_icon_name("open-menu-symbolic");
+    settings_button.set_tooltip_text(Some("Settings"));
+
+    // Buttons start disabled until a directory is selected.
+    scan_button.set_sensitive(false);
+    clear_button.set_sensitive(false);
+    scan_button.add_css_class("suggested-action");
+
+    // Pack the buttons into the toolbar, with the scan controls and dialogs at the end.
+    let toolbar = Box::new(Horizontal, 5);
+    toolbar.add_css_class("toolbar");
+    let spacer = Box::new(Horizontal, 0);
+    spacer.set_hexpand(true);
+    for button in [open_button.upcast_ref::<Widget>(), roots_button.upcast_ref(), clear_button.upcast_ref(),
+                   move_button.upcast_ref(), rename_button.upcast_ref(), remove_album_button.upcast_ref(),
+                   spacer.upcast_ref(), folder_view_button.upcast_ref(), watch_button.upcast_ref(),
+                   pause_button.upcast_ref(), scan_button.upcast_ref(), policy_button.upcast_ref(),
+                   statistics_button.upcast_ref(), history_button.upcast_ref(), settings_button.upcast_ref()] {
+        toolbar.append(button);
+    }
+
+    // Create the layout of the workspace.
+    let workspace = Box::new(Vertical, 0);
+    workspace.append(&toolbar);
+
+    // Create the progress bar, initially hidden.
+    let progress_bar = ProgressBar::new();
+    progress_bar.set_visible(false);
+    workspace.append(&progress_bar);
+
+    // Create a scrollable area for the results view.
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_hexpand(true);
+    scrolled.set_vexpand(true);
+    scrolled.set_policy(Never, Automatic);
+    scrolled.set_propagate_natural_height(true);
+    scrolled.set_valign(Fill);
+    scrolled.set_size_request(-1, 250); // Set a minimum height for the scrolled window.
+
+    // Create the column view for displaying results.
+    let (column_view, list_store, selection_model, filter_model) = create_column_view();
+    let viewport = Viewport::new(None::<&Adjustment>, None::<&Adjustment>);
+    viewport.set_hexpand(true);
+    viewport.set_vexpand(true);
+    viewport.set_valign(Fill);
+    viewport.set_child(Some(&column_view));
+    scrolled.set_child(Some(&viewport));
+
+    // The folder view can be shown instead of the flat list. The selection of the hidden
+    // list is cleared, so that the detail pane does not show a log that is not selected.
+    let (folder_view, folder_column_view, folder_selection) = create_folder_view(&list_store, &app_state);
+    let results_stack = Stack::new();
+    results_stack.add_named(&scrolled, Some("list"));
+    results_stack.add_named(&folder_view, Some("folders"));
+    folder_view_button.connect_toggled(clone!(@weak results_stack, @weak selection_model => move |button| {
+        results_stack.set_visible_child_name(if button.is_active() { "folders" } else { "list" });
+        if button.is_active() {
+            selection_model.unselect_all();
+        }
+    }));
+    let views = ResultsViews {
+        stack: results_stack.clone(),
+        list: column_view.clone(),
+        list_selection: selection_model.clone(),
+        folders: folder_column_view,
+        folder_selection,
+    };
+
+    // Show the selected log below the results, in a pane whose height the user can adjust.
+    let detail_pane = create_detail_pane(&selection_model);
+    detail_pane.set_size_request(-1, 200);
+    let paned = Paned::new(Vertical);
+    paned.set_start_child(Some(&results_stack));
+    paned.set_end_child(Some(&detail_pane));
+    paned.set_shrink_start_child(false);
+    paned.set_shrink_end_child(false);
+    paned.set_resize_end_child(false);
+    paned.set_vexpand(true);
+    toast_overlay.set_child(Some(&paned));
+    workspace.append(&toast_overlay);
+
+    // Create a label to display the file count.
+    let file_count_label = Label::new(Some("Files: 0"));
+    file_count_label.set_halign(Start);
+    file_count_label.set_margin_start(10);
+    file_count_label.set_margin_end(10);
+    file_count_label.set_margin_bottom(5);
+    workspace.append(&file_count_label);
+
+    // The actions of this workspace, which the window forwards its shortcuts to.
+    let actions = SimpleActionGroup::new();
+    workspace.insert_action_group("tab", Some(&actions));
+
+    // Add the search bar above the results, which also keeps the file count label up to date.
+    let search_bar = create_search_bar(workspace.upcast_ref(), &actions, &filter_model, &list_store, &file_count_label);
+    workspace.insert_child_after(&search_bar, Some(&progress_bar));
+
+    let page = tab_view.append(&workspace);
+    page.set_title("New Tab");
+    tab_view.set_selected_page(&page);
+
+    // Set up event handlers for keyboard, mouse (including the context menu), and button clicks.
+    setup_keyboard_controls(workspace.upcast_ref(), &views, &list_store, &app_state);
+    setup_vim_controls(&actions, &views, &detail_pane, &list_store, &app_state);
+    setup_mouse_controls(&column_view, &selection_model);
+    setup_context_menu(&column_view, &selection_model, &list_store, &app_state);
+    setup_drop_target(workspace.upcast_ref(), &roots_list, &list_store, &app_state);
+    setup_watch_button(&watch_button, &list_store, &app_state);
+    setup_read_only_indicator(workspace.upcast_ref(), &page, &[move_button.clone(), rename_button.clone(), remove_album_button.clone()]);
+    let controls = WorkspaceControls {
+        workspace: workspace.clone().upcast(),
+        open_button,
+        scan_button,
+        pause_button,
+        clear_button,
+        roots_list: roots_list.clone(),
+        progress_bar: progress_bar.clone(),
+    };
+    setup_button_actions(&controls, &list_store, &app_state);
+
+    // Name the tab after its scan roots, and show a spinner in it while it is scanning.
+    list_store.connect_items_changed(clone!(@weak page, @strong app_state => move |_, _, _, _| {
+        let Ok(state) = app_state.lock() else {
+            return;
+        };
+        let names: Vec<String> = state.roots.iter()
+            .map(|root| root.file_name().map_or_else(|| root.display().to_string(), |name| name.to_string_lossy().into_owned()))
+            .collect();
+        page.set_title(&if names.is_empty() { "New Tab".to_string() } else { names.join(", ") });
+        let paths: Vec<String> = state.roots.iter().map(|root| root.display().to_string()).collect();
+        page.set_tooltip(&paths.join("\n"));
+    }));
+    progress_bar.connect_visible_notify(clone!(@weak page => move |progress_bar| {
+        page.set_loading(progress_bar.is_visible());
+    }));
+
+    // Connect the settings button to show the settings dialog.
+    settings_button.connect_clicked(clone!(@weak workspace => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_settings_dialog(&window);
+        }
+    }));
+
+    // Connect the history button to show the journal of file operations.
+    history_button.connect_clicked(clone!(@weak workspace, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_history_window(&window, &list_store, &app_state);
+        }
+    }));
+
+    // Connect the statistics button to show the dashboard of the current results.
+    statistics_button.connect_clicked(clone!(@weak workspace, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_statistics_window(&window, workspace.upcast_ref(), &list_store, &app_state);
+        }
+    }));
+
+    // Connect the policy button to show the retention policy editor.
+    policy_button.connect_clicked(clone!(@weak workspace, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_policy_dialog(&window, &list_store, &app_state);
+        }
+    }));
+
+    // Connect the move button to show the "Move to…" dialog for the selection.
+    move_button.connect_clicked(clone!(@weak workspace, @strong views, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_move_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+        }
+    }));
+
+    // Connect the rename button to show the batch rename dialog for the selection.
+    rename_button.connect_clicked(clone!(@weak workspace, @strong views, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_rename_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+        }
+    }));
+
+    // Connect the remove album button to show the dry-run preview for the selection.
+    remove_album_button.connect_clicked(clone!(@weak workspace, @strong views, @strong list_store, @strong app_state => move |_| {
+        if let Some(window) = workspace_window(&workspace) {
+            show_remove_album_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+        }
+    }));
+
+    // When the tab or its window is closed, remember the column layout and stop the scan and watcher.
+    workspace.connect_destroy(clone!(@strong column_view, @strong watch_button, @strong app_state => move |_| {
+        save_column_layout(&column_view);
+        watch_button.set_active(false);
+        if let Ok(mut state) = app_state.lock()
+            && let Some(control) = state.scan_control.take() {
+            control.cancel();
+        }
+    }));
+
+    page
+}
@@ -0,0 +1,28 @@
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+use glib::{user_config_dir, KeyFile, KeyFileFlags};
+
+/// Returns the path of a configuration file in the application's XDG config directory.
+pub fn config_path(file_name: &str) -> PathBuf {
+    user_config_dir().join("drlogseeker").join(file_name)
+}
+
+/// Loads a configuration file, or returns an empty `KeyFile` if it does not exist yet.
+pub fn load_key_file(file_name: &str) -> KeyFile {
+    let key_file = KeyFile::new();
+    let _ = key_file.load_from_file(config_path(file_name), KeyFileFlags::NONE);
+    key_file
+}
+
+/// Saves a configuration file, creating the config directory if necessary.
+///
+/// Returns a message naming the file if it could not be saved. Callers only report it, as
+/// losing a preference should never interrupt the user.
+pub fn save_key_file(file_name: &str, key_file: &KeyFile) -> Result<(), String> {
+    let path = config_path(file_name);
+    path.parent().map_or(Ok(()), create_dir_all)
+        .map_err(|err| err.to_string())
+        .and_then(|_| key_file.save_to_file(&path).map_err(|err| err.to_string()))
+        .map_err(|err| format!("Failed to save {}: {}", path.display(), err))
+}
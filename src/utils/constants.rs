@@ -50,4 +50,27 @@ pub const DR_COLORS: [(u8, u8, u8); 15] = [
     (144, 255, 0),   // DR12 - #90ff00
     (72, 255, 0),    // DR13 - #48ff00
     (0, 255, 0),     // DR14 - #00ff00
-];
\ No newline at end of file
+];
+
+/// Returns the audio health tier for a DR value, as listed in the README.
+///
+/// - **DR0-7**: Very Compressed
+/// - **DR8**: Highly Compressed
+/// - **DR9**: Compressed
+/// - **DR10**: Slightly Compressed
+/// - **DR11**: Moderate
+/// - **DR12**: Good
+/// - **DR13**: Very Good
+/// - **DR14+**: Excellent
+pub fn dr_tier_name(dr: u8) -> &'static str {
+    match dr {
+        0..=7 => "Very Compressed",
+        8 => "Highly Compressed",
+        9 => "Compressed",
+        10 => "Slightly Compressed",
+        11 => "Moderate",
+        12 => "Good",
+        13 => "Very Good",
+        _ => "Excellent",
+    }
+}
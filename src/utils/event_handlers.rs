@@ -1,37 +1,79 @@
+use std::cell::Cell;
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use gio::ListStore;
+use gio::{ListStore, SimpleAction, SimpleActionGroup};
 use glib::clone;
 use glib::Propagation::{Proceed, Stop};
-use gtk4::{ColumnView, EventControllerKey, GestureClick, MultiSelection, PropagationPhase::Capture, StringObject};
-use gtk4::gdk::{Key, ModifierType};
-use libadwaita::{ApplicationWindow};
-use libadwaita::prelude::{Cast, EventControllerExt, GestureSingleExt, ListModelExt, SelectionModelExt, WidgetExt};
+use gtk4::{ColumnView, DropTarget, EventControllerKey, GestureClick, ListBox, ListView, MultiSelection, Notebook, PropagationPhase::Capture, StringObject, Text, Widget};
+use gtk4::gdk::{DragAction, FileList, Key, ModifierType};
+use libadwaita::{ApplicationWindow, TabView};
+use libadwaita::prelude::{ActionGroupExt, ActionMapExt, Cast, CastNone, EventControllerExt, FileExt, GestureSingleExt, GtkApplicationExt, GtkWindowExt, ListModelExt, ObjectExt, SelectionModelExt, StaticType, ToVariant, WidgetExt};
 
 use crate::data::app_state::AppState;
-use crate::file_manager::file_ops::{delete_selected_files, try_open_file};
+use crate::dr_analyzer::analyzer::update_rows;
+use crate::file_manager::file_ops::{remove_files, try_open_file};
+use crate::ui::album_dialog::show_remove_album_dialog;
+use crate::ui::context_menu::add_action;
+use crate::ui::move_dialog::show_move_dialog;
+use crate::ui::rename_dialog::show_rename_dialog;
+use crate::ui::scan_roots::add_scan_roots;
+use crate::ui::workspace::{workspace_window, ResultsViews};
 
-/// Sets up global keyboard shortcuts for the application window.
+/// The window actions of the vim-like controls and their accelerators.
+const VIM_ACCELS: [(&str, &str); 7] = [
+    ("next-row", "j"),
+    ("previous-row", "k"),
+    ("first-row", "g"),
+    ("last-row", "<Shift>g"),
+    ("search", "slash"),
+    ("toggle-mark", "x"),
+    ("remove-row", "d"),
+];
+
+/// How long "remove-row" waits for its second key press, as in vim's "dd".
+const DOUBLE_PRESS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sets up the keyboard shortcuts of a workspace.
 ///
-/// This function attaches an `EventControllerKey` to the main window to handle
-/// the following actions:
+/// This function attaches an `EventControllerKey` to a workspace (see `add_workspace`) to
+/// handle the following actions while the focus is inside it:
 /// - **Ctrl+A**: Selects all items in the `ColumnView`.
+/// - **Ctrl+M**: Opens the "Move to…" dialog for the selected items.
+/// - **F2**: Opens the batch rename dialog for the album folders of the selected items.
 /// - **Delete**: Deletes the selected items, potentially from the filesystem based on settings.
+/// - **Shift+Delete**: Opens the dry-run preview for removing the album folders of the selected items.
 /// - **Enter**: Opens the selected file(s) using the system's default application.
 ///
 /// The controller operates in the `Capture` phase to ensure these shortcuts are
-/// handled before any other widget-specific key events.
-pub fn setup_keyboard_controls(window: &ApplicationWindow, selection_model: &MultiSelection, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+/// handled before any other widget-specific key events. Keys pressed while a text field
+/// such as the search entry has the focus are left to that field.
+///
+/// The shortcuts act on the results view that is shown (see `ResultsViews`). In the folder
+/// view, the file operations act on all logs beneath the selected folders, and Enter is left
+/// to the folder view.
+pub fn setup_keyboard_controls(workspace: &Widget, views: &ResultsViews, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
     let key_controller = EventControllerKey::new();
     key_controller.set_propagation_phase(Capture);
-    window.add_controller(key_controller.clone());
-    key_controller.connect_key_pressed(clone!(@weak window, @weak selection_model, @weak list_store, @weak app_state => 
+    workspace.add_controller(key_controller.clone());
+    key_controller.connect_key_pressed(clone!(@weak workspace, @strong views, @weak list_store, @weak app_state => 
         @default-return Proceed, move |_controller, key, _keycode, modifier_state| {
+            let Some(window) = workspace_window(&workspace) else {
+                return Proceed;
+            };
+
+            // Leave keys to text fields such as the search entry.
+            if GtkWindowExt::focus(&window).is_some_and(|widget| widget.downcast_ref::<Text>().is_some()) {
+                return Proceed;
+            }
             match key {
 
                 // Ctrl+A: Select all items.
                 Key::a | Key::A if modifier_state.bits() & ModifierType::CONTROL_MASK.bits() != 0 => {
+                    let (_, selection_model) = views.shown();
                     selection_model.unselect_all();
                     for i in 0..selection_model.n_items() {
                         selection_model.select_item(i, false);
@@ -39,24 +81,45 @@ pub fn setup_keyboard_controls(window: &ApplicationWindow, selection_model: &Mul
                     Stop // Stop propagation to prevent other widgets from handling it.
                 }
 
+                // Ctrl+M: Move selected items into destination folders.
+                Key::m | Key::M if modifier_state.bits() & ModifierType::CONTROL_MASK.bits() != 0 => {
+                    show_move_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+                    Stop
+                }
+
+                // F2: Rename the album folders of the selected items.
+                Key::F2 => {
+                    show_rename_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+                    Stop
+                }
+
+                // Shift+Delete: Remove the album folders of the selected items.
+                Key::Delete if modifier_state.bits() & ModifierType::SHIFT_MASK.bits() != 0 => {
+                    show_remove_album_dialog(&window, views.selected_logs(&app_state), &list_store, &app_state);
+                    Stop
+                }
+
                 // Delete: Remove selected items.
                 Key::Delete => {
-                    delete_selected_files(&window, &selection_model, &list_store, &app_state);
+                    let logs = views.selected_logs(&app_state);
+                    if !logs.is_empty() {
+                        remove_files(&window, logs, &list_store, &app_state);
+                    }
                     Stop
                 }
 
                 // Enter: Open selected files.
-                Key::Return | Key::KP_Enter | Key::ISO_Enter => {
+                Key::Return | Key::KP_Enter | Key::ISO_Enter if !views.folders_shown() => {
+                    let selection_model = &views.list_selection;
                     let selected_indices: Vec<u32> = (0..selection_model.n_items())
                         .filter(|&i| selection_model.is_selected(i))
                         .collect();
                     if !selected_indices.is_empty() {
                         for index in selected_indices {
-                            if let Some(item) = selection_model.item(index) {
-                                if let Some(string_obj) = item.downcast_ref::<StringObject>() {
-                                    let path = PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""));
-                                    try_open_file(&window, &path);
-                                }
+                            if let Some(item) = selection_model.item(index)
+                                && let Some(string_obj) = item.downcast_ref::<StringObject>() {
+                                let path = PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""));
+                                try_open_file(&window, &path);
                             }
                         }
                         Stop
@@ -73,27 +136,196 @@ pub fn setup_keyboard_controls(window: &ApplicationWindow, selection_model: &Mul
 ///
 /// This function attaches a `GestureClick` controller to the `ColumnView` to detect
 /// a double-click with the left mouse button, which triggers opening the selected file.
-pub fn setup_mouse_controls(column_view: &ColumnView, window: &ApplicationWindow, selection_model: &MultiSelection) {
+pub fn setup_mouse_controls(column_view: &ColumnView, selection_model: &MultiSelection) {
     let gesture_click = GestureClick::new();
     gesture_click.set_button(1); // Primary (left) mouse button.
     column_view.add_controller(gesture_click.clone());
     
     // Use the capture phase to handle the event early.
     gesture_click.set_propagation_phase(Capture);
-    gesture_click.connect_released(clone!(@weak window, @weak selection_model => move |gesture, n_press, _x, _y| {
+    gesture_click.connect_released(clone!(@weak column_view, @weak selection_model => move |gesture, n_press, _x, _y| {
 
         // Check for a double-click (n_press == 2).
-        if gesture.current_button() == 1 && n_press == 2 {
+        if gesture.current_button() == 1 && n_press == 2
+            && let Some(window) = workspace_window(&column_view) {
 
             // Find the first selected item and open it.
-            if let Some(index) = (0..selection_model.n_items()).find(|&i| selection_model.is_selected(i)) {
-                if let Some(item) = selection_model.item(index) {
-                    if let Some(string_obj) = item.downcast_ref::<StringObject>() {
-                        let path = PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""));
-                        try_open_file(&window, &path);
-                    }
-                }
+            if let Some(index) = (0..selection_model.n_items()).find(|&i| selection_model.is_selected(i))
+                && let Some(item) = selection_model.item(index)
+                && let Some(string_obj) = item.downcast_ref::<StringObject>() {
+                let path = PathBuf::from(string_obj.string().split('\t').nth(1).unwrap_or(""));
+                try_open_file(&window, &path);
             }
         }
     }));
+}
+
+/// Allows folders, archives and log files to be dropped onto a workspace from a file manager.
+///
+/// A `DropTarget` accepting a `gdk::FileList` is attached to the workspace. Every dropped item
+/// is added as a separate scan root, and its logs are merged into the results list.
+pub fn setup_drop_target(workspace: &Widget, roots_list: &ListBox, list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+    let drop_target = DropTarget::new(FileList::static_type(), DragAction::COPY);
+    workspace.add_controller(drop_target.clone());
+    drop_target.connect_drop(clone!(@weak workspace, @weak roots_list, @weak list_store, @strong app_state =>
+        @default-return false, move |_target, value, _x, _y| {
+            let (Ok(file_list), Some(window)) = (value.get::<FileList>(), workspace_window(&workspace)) else {
+                return false;
+            };
+            let paths: Vec<PathBuf> = file_list.files().iter().filter_map(|file| file.path()).collect();
+            if paths.is_empty() {
+                return false;
+            }
+            add_scan_roots(&window, paths, &roots_list, &list_store, &app_state);
+            true
+    }));
+}
+
+/// Sets up the vim-like controls of the results list as actions of a workspace.
+///
+/// The actions are added to the "tab" action group of the workspace, and the window forwards
+/// its accelerators to the selected tab (see `setup_vim_accels`):
+/// - **j** / **k**: Selects the next or previous row.
+/// - **g** / **G**: Selects the first or last row.
+/// - **/**: Opens the search bar (the "search" action of `create_search_bar`).
+/// - **x**: Marks the selected rows, or unmarks them if they are all marked already.
+/// - **dd**: Removes the selected rows, like the Delete key.
+/// - **Space**: Shows or hides the detail pane. Unlike the other keys, this is only handled
+///   while the results list has the focus, so that Space still activates buttons.
+///
+/// Like the other shortcuts, they act on the results view that is shown (see `ResultsViews`).
+pub fn setup_vim_controls(actions: &SimpleActionGroup, views: &ResultsViews, detail_pane: &Notebook,
+                          list_store: &ListStore, app_state: &Arc<Mutex<AppState>>) {
+
+    // "d" only removes the rows when it is pressed twice in a row, without another vim key
+    // in between, so that "d j d" does not remove the newly selected row.
+    let last_press: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    let add_vim_action = |name: &str, activate: Box<dyn Fn()>| {
+        add_action(actions, name, clone!(@strong last_press => move || {
+            last_press.set(None);
+            activate();
+        }));
+    };
+    add_vim_action("next-row", Box::new(clone!(@strong views => move || {
+        let (column_view, selection_model) = views.shown();
+        let selection = selection_model.selection();
+        let next = if selection.is_empty() { 0 } else { selection.maximum().saturating_add(1) };
+        select_row(column_view, selection_model, next);
+    })));
+    add_vim_action("previous-row", Box::new(clone!(@strong views => move || {
+        let (column_view, selection_model) = views.shown();
+        let selection = selection_model.selection();
+        let previous = if selection.is_empty() { 0 } else { selection.minimum().saturating_sub(1) };
+        select_row(column_view, selection_model, previous);
+    })));
+    add_vim_action("first-row", Box::new(clone!(@strong views => move || {
+        let (column_view, selection_model) = views.shown();
+        select_row(column_view, selection_model, 0);
+    })));
+    add_vim_action("last-row", Box::new(clone!(@strong views => move || {
+        let (column_view, selection_model) = views.shown();
+        select_row(column_view, selection_model, u32::MAX);
+    })));
+    add_vim_action("toggle-mark", Box::new(clone!(@strong views, @weak list_store, @strong app_state => move || {
+        let paths: HashSet<PathBuf> = views.selected_logs(&app_state).into_iter().collect();
+        if paths.is_empty() {
+            return;
+        }
+        if let Ok(mut state) = app_state.lock() {
+            let mark = !state.results.iter().filter(|result| paths.contains(&result.path)).all(|result| result.marked);
+            let mut changed = Vec::new();
+            for result in state.results.iter_mut().filter(|result| paths.contains(&result.path)) {
+                result.marked = mark;
+                changed.push(result.clone());
+            }
+            update_rows(&list_store, &[], &changed);
+        }
+    })));
+
+    add_vim_action("toggle-details", Box::new(clone!(@weak detail_pane => move || {
+        detail_pane.set_visible(!detail_pane.is_visible());
+    })));
+    if let Some(search_action) = actions.lookup_action("search").and_downcast::<SimpleAction>() {
+        search_action.connect_activate(clone!(@strong last_press => move |_, _| last_press.set(None)));
+    }
+    let remove_action = SimpleAction::new("remove-row", None);
+    remove_action.connect_activate(clone!(@strong views, @weak list_store, @strong app_state => move |_, _| {
+        if last_press.take().is_some_and(|pressed| pressed.elapsed() < DOUBLE_PRESS_TIMEOUT)
+            && let Some(window) = workspace_window(&views.list) {
+            let logs = views.selected_logs(&app_state);
+            if !logs.is_empty() {
+                remove_files(&window, logs, &list_store, &app_state);
+            }
+        } else {
+            last_press.set(Some(Instant::now()));
+        }
+    }));
+    actions.add_action(&remove_action);
+
+    // Space is left to buttons and switches elsewhere, and only toggles the details here.
+    for column_view in [&views.list, &views.folders] {
+        let key_controller = EventControllerKey::new();
+        key_controller.set_propagation_phase(Capture);
+        key_controller.connect_key_pressed(clone!(@weak actions => @default-return Proceed, move |_, key, _, modifiers| {
+            if key != Key::space || !modifiers.is_empty() {
+                return Proceed;
+            }
+            actions.activate_action("toggle-details", None);
+            Stop
+        }));
+        column_view.add_controller(key_controller);
+    }
+}
+
+/// Registers the accelerators of the vim-like controls for a window.
+///
+/// The accelerators are registered with the application, so they are listed in the shortcuts
+/// window (see `setup_shortcuts_window`). Each of them activates a window action that forwards
+/// to the action of the same name of the selected tab (see `setup_vim_controls`).
+///
+/// As these are plain keys, the window actions are disabled while a text field such as the
+/// search entry has the focus, so that the keys can be typed.
+pub fn setup_vim_accels(window: &ApplicationWindow, tab_view: &TabView) {
+    for (name, accel) in VIM_ACCELS {
+        let action = SimpleAction::new(name, None);
+        action.connect_activate(clone!(@weak tab_view => move |_, _| {
+            if let Some(page) = tab_view.selected_page() {
+                let _ = page.child().activate_action(&format!("tab.{}", name), None);
+            }
+        }));
+        window.add_action(&action);
+        if let Some(app) = window.application() {
+            app.set_accels_for_action(&format!("win.{}", name), &[accel]);
+        }
+    }
+
+    // Leave plain keys to text fields such as the search entry.
+    window.connect_focus_widget_notify(|window| {
+        let typing = GtkWindowExt::focus(window).is_some_and(|widget| widget.downcast_ref::<Text>().is_some());
+        for (name, _) in VIM_ACCELS {
+            if let Some(action) = window.lookup_action(name).and_downcast::<SimpleAction>() {
+                action.set_enabled(!typing);
+            }
+        }
+    });
+}
+
+/// Selects only the row at `position`, or the last row if there are fewer rows, and scrolls to it.
+fn select_row(column_view: &ColumnView, selection_model: &MultiSelection, position: u32) {
+    let n_items = selection_model.n_items();
+    if n_items == 0 {
+        return;
+    }
+    let position = position.min(n_items - 1);
+    selection_model.select_item(position, true);
+
+    // The rows are shown by a list view inside the column view, which can scroll to an item.
+    let mut child = column_view.first_child();
+    while let Some(widget) = child {
+        if widget.is::<ListView>() {
+            let _ = widget.activate_action("list.scroll-to-item", Some(&position.to_variant()));
+            break;
+        }
+        child = widget.next_sibling();
+    }
 }
\ No newline at end of file
@@ -1,2 +1,3 @@
+pub mod config;
 pub mod constants;
 pub mod event_handlers;
\ No newline at end of file